@@ -0,0 +1,242 @@
+use crate::tabular::Cell;
+use crate::Mission;
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum GroupKey {
+    Destination,
+    Status,
+    Year,
+    CrewSize,
+}
+
+impl GroupKey {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GroupKey::Destination => "destination",
+            GroupKey::Status => "status",
+            GroupKey::Year => "year",
+            GroupKey::CrewSize => "crew_size",
+        }
+    }
+
+    /// The group label for `mission`. Text keys are compared
+    /// case-insensitively, so the returned value is only the lookup key.
+    pub fn key_of(self, mission: &Mission) -> String {
+        match self {
+            GroupKey::Destination => mission.destination.to_ascii_lowercase(),
+            GroupKey::Status => mission.status.to_ascii_lowercase(),
+            GroupKey::Year => mission
+                .date
+                .get(..4)
+                .filter(|y| y.bytes().all(|b| b.is_ascii_digit()))
+                .unwrap_or("unknown")
+                .to_string(),
+            GroupKey::CrewSize => mission.crew_size.to_string(),
+        }
+    }
+
+    /// First-seen spelling for text keys, the key itself otherwise.
+    pub fn label_of(self, mission: &Mission) -> String {
+        match self {
+            GroupKey::Destination => mission.destination.clone(),
+            GroupKey::Status => mission.status.clone(),
+            _ => self.key_of(mission),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Summary {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+    pub p95: f64,
+}
+
+impl Summary {
+    /// Summarises `values`, which must be non-empty. Percentiles use the
+    /// nearest-rank method; the median averages the two middle values.
+    pub fn of(values: &mut [f64]) -> Self {
+        values.sort_by(|a, b| a.total_cmp(b));
+        let n = values.len();
+
+        let median = if n.is_multiple_of(2) {
+            (values[n / 2 - 1] + values[n / 2]) / 2.0
+        } else {
+            values[n / 2]
+        };
+
+        Summary {
+            min: values[0],
+            max: values[n - 1],
+            mean: values.iter().sum::<f64>() / n as f64,
+            median,
+            p95: percentile(values, 95.0),
+        }
+    }
+}
+
+/// Nearest-rank percentile over sorted, non-empty `values`.
+pub fn percentile(sorted: &[f64], pct: f64) -> f64 {
+    let rank = (pct / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupStats {
+    pub group: String,
+    pub count: usize,
+    pub duration: Summary,
+    pub success_rate: Summary,
+}
+
+fn sort_key(key: GroupKey, group: &str) -> (u64, String) {
+    match key {
+        GroupKey::Year | GroupKey::CrewSize => (group.parse().unwrap_or(u64::MAX), group.to_string()),
+        _ => (0, group.to_ascii_lowercase()),
+    }
+}
+
+/// Splits `missions` by `key`, preserving input order within each group.
+/// Groups are ordered numerically for year and crew size and alphabetically
+/// otherwise.
+pub fn partition(missions: &[Mission], key: GroupKey) -> Vec<(String, Vec<&Mission>)> {
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut groups: Vec<(String, Vec<&Mission>)> = Vec::new();
+
+    for mission in missions {
+        let slot = *index.entry(key.key_of(mission)).or_insert_with(|| {
+            groups.push((key.label_of(mission), Vec::new()));
+            groups.len() - 1
+        });
+        groups[slot].1.push(mission);
+    }
+
+    groups.sort_by_key(|(label, _)| sort_key(key, label));
+    groups
+}
+
+pub fn group_missions(missions: &[Mission], key: GroupKey) -> Vec<GroupStats> {
+    partition(missions, key)
+        .into_iter()
+        .map(|(label, members)| {
+            let mut durations: Vec<f64> = members.iter().map(|m| m.duration as f64).collect();
+            let mut rates: Vec<f64> = members.iter().map(|m| m.success_rate).collect();
+            GroupStats {
+                group: label,
+                count: members.len(),
+                duration: Summary::of(&mut durations),
+                success_rate: Summary::of(&mut rates),
+            }
+        })
+        .collect()
+}
+
+pub const HEADERS: [&str; 12] = [
+    "Group",
+    "Count",
+    "Duration Min",
+    "Duration Max",
+    "Duration Mean",
+    "Duration Median",
+    "Duration P95",
+    "Success Rate Min",
+    "Success Rate Max",
+    "Success Rate Mean",
+    "Success Rate Median",
+    "Success Rate P95",
+];
+
+pub fn rows(groups: &[GroupStats]) -> Vec<Vec<Cell>> {
+    groups
+        .iter()
+        .map(|g| {
+            vec![
+                Cell::text(g.group.as_str()),
+                Cell::Int(g.count as i64),
+                Cell::Float(g.duration.min),
+                Cell::Float(g.duration.max),
+                Cell::Float(g.duration.mean),
+                Cell::Float(g.duration.median),
+                Cell::Float(g.duration.p95),
+                Cell::Float(g.success_rate.min),
+                Cell::Float(g.success_rate.max),
+                Cell::Float(g.success_rate.mean),
+                Cell::Float(g.success_rate.median),
+                Cell::Float(g.success_rate.p95),
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mission(line: &str) -> Mission {
+        Mission::from_line(line, 1).unwrap()
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut values: Vec<f64> = (1..=20).map(|v| v as f64).collect();
+        let summary = Summary::of(&mut values);
+
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(summary.max, 20.0);
+        assert_eq!(summary.mean, 10.5);
+        assert_eq!(summary.median, 10.5);
+        assert_eq!(summary.p95, 19.0);
+
+        assert_eq!(Summary::of(&mut [7.0]).p95, 7.0);
+    }
+
+    #[test]
+    fn test_group_missions_by_destination_is_case_insensitive() {
+        let missions = vec![
+            mission("2045-01-01 | A | Mars | Completed | 3 | 100 | 90.0 | ABC-123-XYZ"),
+            mission("2046-01-01 | B | MARS | Failed | 4 | 300 | 50.0 | ABC-123-XYZ"),
+            mission("2046-01-01 | C | Jupiter | Completed | 5 | 900 | 99.0 | ABC-123-XYZ"),
+        ];
+        let groups = group_missions(&missions, GroupKey::Destination);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].group, "Jupiter");
+        assert_eq!(groups[1].group, "Mars");
+        assert_eq!(groups[1].count, 2);
+        assert_eq!(groups[1].duration.mean, 200.0);
+    }
+
+    #[test]
+    fn test_partition_preserves_order() {
+        let missions = vec![
+            mission("2045-01-01 | A | Venus | Completed | 3 | 100 | 90.0 | ABC-123-XYZ"),
+            mission("2045-01-01 | B | Mars | Completed | 3 | 100 | 90.0 | ABC-123-XYZ"),
+            mission("2045-01-01 | C | venus | Completed | 3 | 100 | 90.0 | ABC-123-XYZ"),
+        ];
+        let groups = partition(&missions, GroupKey::Destination);
+
+        assert_eq!(groups[0].0, "Mars");
+        assert_eq!(groups[1].0, "Venus");
+        let ids: Vec<&str> = groups[1].1.iter().map(|m| m.mission_id.as_str()).collect();
+        assert_eq!(ids, vec!["A", "C"]);
+    }
+
+    #[test]
+    fn test_group_missions_by_year_sorted_numerically() {
+        let missions = vec![
+            mission("2046-01-01 | A | Mars | Completed | 3 | 100 | 90.0 | ABC-123-XYZ"),
+            mission("bad-date | B | Mars | Completed | 3 | 100 | 90.0 | ABC-123-XYZ"),
+            mission("2045-01-01 | C | Mars | Completed | 3 | 100 | 90.0 | ABC-123-XYZ"),
+        ];
+        let groups: Vec<String> = group_missions(&missions, GroupKey::Year)
+            .into_iter()
+            .map(|g| g.group)
+            .collect();
+
+        assert_eq!(groups, vec!["2045", "2046", "unknown"]);
+    }
+}
@@ -0,0 +1,256 @@
+use crate::aggregate::{self, GroupKey};
+use crate::tabular::Cell;
+use crate::Mission;
+use serde::Serialize;
+
+/// Groups smaller than this are too small for robust statistics.
+const MIN_GROUP_SIZE: usize = 5;
+
+/// Scales MAD so the robust z-score matches a standard z-score for normal
+/// data (Iglewicz and Hoaglin).
+const MAD_SCALE: f64 = 0.6745;
+
+/// Scales the mean absolute deviation the same way, for groups where more
+/// than half the values are identical and MAD is 0.
+const MEAN_AD_SCALE: f64 = 0.7979;
+
+pub const DEFAULT_MAD_THRESHOLD: f64 = 3.5;
+pub const DEFAULT_IQR_THRESHOLD: f64 = 1.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Method {
+    /// Median absolute deviation; flags |robust z| above the threshold
+    Mad,
+    /// Interquartile range; flags values beyond threshold * IQR of the quartiles
+    Iqr,
+}
+
+impl Method {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Method::Mad => "mad",
+            Method::Iqr => "iqr",
+        }
+    }
+
+    pub fn default_threshold(self) -> f64 {
+        match self {
+            Method::Mad => DEFAULT_MAD_THRESHOLD,
+            Method::Iqr => DEFAULT_IQR_THRESHOLD,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Anomaly {
+    pub line_number: usize,
+    pub mission_id: String,
+    pub destination: String,
+    pub field: &'static str,
+    pub value: f64,
+    pub median: f64,
+    /// Robust z-score; `None` when the group has no spread at all.
+    pub z_score: Option<f64>,
+    pub reason: String,
+}
+
+struct FieldStats {
+    median: f64,
+    mad: f64,
+    /// Mean absolute deviation from the median.
+    mean_ad: f64,
+    q1: f64,
+    q3: f64,
+}
+
+fn median_of(sorted: &[f64]) -> f64 {
+    let n = sorted.len();
+    if n.is_multiple_of(2) {
+        (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0
+    } else {
+        sorted[n / 2]
+    }
+}
+
+fn field_stats(values: &[f64]) -> FieldStats {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let median = median_of(&sorted);
+
+    let mut deviations: Vec<f64> = sorted.iter().map(|v| (v - median).abs()).collect();
+    deviations.sort_by(|a, b| a.total_cmp(b));
+
+    FieldStats {
+        median,
+        mad: median_of(&deviations),
+        mean_ad: deviations.iter().sum::<f64>() / deviations.len() as f64,
+        q1: aggregate::percentile(&sorted, 25.0),
+        q3: aggregate::percentile(&sorted, 75.0),
+    }
+}
+
+fn robust_z(value: f64, stats: &FieldStats) -> Option<f64> {
+    if stats.mad != 0.0 {
+        Some(MAD_SCALE * (value - stats.median) / stats.mad)
+    } else if stats.mean_ad != 0.0 {
+        Some(MEAN_AD_SCALE * (value - stats.median) / stats.mean_ad)
+    } else {
+        None
+    }
+}
+
+type FieldFn = fn(&Mission) -> f64;
+
+const FIELDS: [(&str, FieldFn); 3] = [
+    ("duration", |m| m.duration as f64),
+    ("crew_size", |m| m.crew_size as f64),
+    ("success_rate", |m| m.success_rate),
+];
+
+/// Flags values that are outliers relative to other missions with the
+/// same destination. A mission can be flagged once per field.
+pub fn detect(missions: &[Mission], method: Method, threshold: f64) -> Vec<Anomaly> {
+    let mut anomalies = Vec::new();
+
+    for (_, members) in aggregate::partition(missions, GroupKey::Destination) {
+        if members.len() < MIN_GROUP_SIZE {
+            continue;
+        }
+
+        for (field, get) in FIELDS {
+            let values: Vec<f64> = members.iter().map(|m| get(m)).collect();
+            let stats = field_stats(&values);
+
+            for (mission, &value) in members.iter().zip(&values) {
+                let z_score = robust_z(value, &stats);
+
+                let reason = match method {
+                    Method::Mad => match z_score {
+                        Some(z) if z.abs() > threshold => format!(
+                            "{} {} deviates from the {} median {} (robust z {:.1})",
+                            field, value, mission.destination, stats.median, z
+                        ),
+                        _ => continue,
+                    },
+                    Method::Iqr => {
+                        let iqr = stats.q3 - stats.q1;
+                        let (low, high) = (stats.q1 - threshold * iqr, stats.q3 + threshold * iqr);
+                        if value > high {
+                            format!("{} {} is above the {} upper fence {}", field, value, mission.destination, high)
+                        } else if value < low {
+                            format!("{} {} is below the {} lower fence {}", field, value, mission.destination, low)
+                        } else {
+                            continue;
+                        }
+                    }
+                };
+
+                anomalies.push(Anomaly {
+                    line_number: mission.line_number,
+                    mission_id: mission.mission_id.clone(),
+                    destination: mission.destination.clone(),
+                    field,
+                    value,
+                    median: stats.median,
+                    z_score,
+                    reason,
+                });
+            }
+        }
+    }
+
+    anomalies.sort_by_key(|a| a.line_number);
+    anomalies
+}
+
+pub const HEADERS: [&str; 8] = [
+    "Line",
+    "Mission ID",
+    "Destination",
+    "Field",
+    "Value",
+    "Median",
+    "Z-Score",
+    "Reason",
+];
+
+pub fn rows(anomalies: &[Anomaly]) -> Vec<Vec<Cell>> {
+    anomalies
+        .iter()
+        .map(|a| {
+            vec![
+                Cell::Int(a.line_number as i64),
+                Cell::text(a.mission_id.as_str()),
+                Cell::text(a.destination.as_str()),
+                Cell::text(a.field),
+                Cell::Float(a.value),
+                Cell::Float(a.median),
+                a.z_score.map_or(Cell::Null, Cell::Float),
+                Cell::text(a.reason.as_str()),
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn missions() -> Vec<Mission> {
+        let durations = [400, 410, 390, 405, 395, 420, 90000];
+        let mut missions: Vec<Mission> = durations
+            .iter()
+            .enumerate()
+            .map(|(idx, d)| {
+                let line = format!("2045-01-01 | M{} | Mars | Completed | 4 | {} | 90.0 | ABC-123-XYZ", idx, d);
+                Mission::from_line(&line, idx + 1).unwrap()
+            })
+            .collect();
+        // Too small a group to judge
+        missions.push(Mission::from_line("2045-01-01 | X | Moon | Completed | 4 | 99999 | 90.0 | ABC-123-XYZ", 99).unwrap());
+        missions
+    }
+
+    #[test]
+    fn test_detect_mad() {
+        let anomalies = detect(&missions(), Method::Mad, DEFAULT_MAD_THRESHOLD);
+
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].line_number, 7);
+        assert_eq!(anomalies[0].field, "duration");
+        assert_eq!(anomalies[0].median, 405.0);
+        assert!(anomalies[0].z_score.unwrap() > 1000.0);
+    }
+
+    #[test]
+    fn test_detect_iqr() {
+        let anomalies = detect(&missions(), Method::Iqr, DEFAULT_IQR_THRESHOLD);
+
+        assert_eq!(anomalies.len(), 1);
+        assert!(anomalies[0].reason.contains("upper fence"));
+    }
+
+    #[test]
+    fn test_zero_mad_falls_back_to_mean_deviation() {
+        let missions: Vec<Mission> = [400, 400, 400, 400, 400, 410, 999999]
+            .iter()
+            .enumerate()
+            .map(|(idx, d)| {
+                let line = format!("2045-01-01 | M{} | Mars | Completed | 4 | {} | 90.0 | ABC-123-XYZ", idx, d);
+                Mission::from_line(&line, idx + 1).unwrap()
+            })
+            .collect();
+
+        let anomalies = detect(&missions, Method::Mad, DEFAULT_MAD_THRESHOLD);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].line_number, 7);
+        assert_eq!(anomalies[0].median, 400.0);
+    }
+
+    #[test]
+    fn test_no_spread_is_not_flagged_by_mad() {
+        // Crew size and success rate are constant across the group
+        let anomalies = detect(&missions(), Method::Mad, DEFAULT_MAD_THRESHOLD);
+        assert!(anomalies.iter().all(|a| a.field == "duration"));
+    }
+}
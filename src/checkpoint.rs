@@ -0,0 +1,309 @@
+use crate::hash::{fnv1a, prefix_hash, FNV_OFFSET};
+use crate::validation::Config;
+use crate::{count_read_error, scan_line, Mission, Statistics};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever the checkpoint layout or the meaning of its fields changes.
+const VERSION: u32 = 2;
+
+/// Everything needed to pick up a ranking run where the last one stopped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    version: u32,
+    /// Fingerprint of the `--config` and `--rules` files in effect.
+    settings: u64,
+    /// Byte offset just past the last complete line scanned.
+    offset: u64,
+    /// FNV-1a of the first `offset` bytes of the log, extended over the
+    /// appended bytes by each run.
+    prefix_hash: u64,
+    /// How many leaders are kept; a run asking for more must rescan.
+    top: usize,
+    statistics: Statistics,
+    /// The best `top` missions so far, longest first.
+    leaders: Vec<Mission>,
+}
+
+/// Fingerprints the contents of the settings files, so a checkpoint taken
+/// under other ranges or rules is not reused.
+pub fn settings_fingerprint(paths: &[Option<&PathBuf>]) -> Result<u64, String> {
+    let mut hash = FNV_OFFSET;
+    for path in paths {
+        match path {
+            Some(path) => {
+                let bytes = fs::read(path)
+                    .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+                hash = fnv1a(hash, &(bytes.len() as u64).to_le_bytes());
+                hash = fnv1a(hash, &bytes);
+            }
+            None => hash = fnv1a(hash, &[0xff]),
+        }
+    }
+    Ok(hash)
+}
+
+impl Checkpoint {
+    /// Reads a checkpoint, or `None` if there is none yet.
+    pub fn load(path: &Path) -> Result<Option<Checkpoint>, String> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("Failed to read state file {}: {}", path.display(), e)),
+        };
+        serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|e| format!("Invalid state file {}: {}", path.display(), e))
+    }
+
+    /// Writes the checkpoint next to `path` first and renames it into place,
+    /// so an interrupted run leaves the previous checkpoint intact.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string(self)
+            .map_err(|e| format!("Failed to serialize state: {}", e))?;
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        fs::write(&tmp, json)
+            .and_then(|_| fs::rename(&tmp, path))
+            .map_err(|e| format!("Failed to write state file {}: {}", path.display(), e))
+    }
+
+    /// Why this checkpoint cannot be resumed against `file`, if it cannot.
+    fn stale_reason(&self, file: &mut File, settings: u64, top: usize) -> io::Result<Option<&'static str>> {
+        if self.version != VERSION {
+            return Ok(Some("it was written by another version"));
+        }
+        if self.settings != settings {
+            return Ok(Some("--config or --rules changed"));
+        }
+        if top > self.top {
+            return Ok(Some("--top is larger than the checkpoint keeps"));
+        }
+        if file.metadata()?.len() < self.offset {
+            return Ok(Some("the log is shorter than the scanned part"));
+        }
+        if prefix_hash(file, self.offset)? != self.prefix_hash {
+            return Ok(Some("the scanned part of the log was rewritten"));
+        }
+        Ok(None)
+    }
+}
+
+fn rank(leaders: &mut Vec<Mission>, top: usize) {
+    leaders.sort_by_key(|m| Reverse(m.duration));
+    leaders.truncate(top);
+}
+
+/// Ranks `file_path` like a full scan would, but starts from the checkpoint
+/// in `state_path` when the log has only grown since, then saves a new one.
+/// A trailing line without a newline is ranked but left out of the
+/// checkpoint, since it may still be being written.
+pub fn scan_incremental(
+    file_path: &Path,
+    state_path: &Path,
+    config: &Config,
+    settings: u64,
+    top: usize,
+    verbose: bool,
+) -> Result<(Vec<Mission>, Statistics), String> {
+    let mut file = File::open(file_path)
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+
+    let mut stats = Statistics::default();
+    let mut leaders = Vec::new();
+    let mut offset = 0;
+    let mut hash = FNV_OFFSET;
+
+    if let Some(checkpoint) = Checkpoint::load(state_path)? {
+        let stale = checkpoint
+            .stale_reason(&mut file, settings, top)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        match stale {
+            Some(reason) => {
+                if verbose {
+                    eprintln!("Warning: Ignoring {} because {}; rescanning from the start", state_path.display(), reason);
+                }
+            }
+            None => {
+                if verbose {
+                    eprintln!("Resuming after line {} (byte {}) from {}",
+                             checkpoint.statistics.total_lines, checkpoint.offset, state_path.display());
+                }
+                stats = checkpoint.statistics;
+                leaders = checkpoint.leaders;
+                offset = checkpoint.offset;
+                hash = checkpoint.prefix_hash;
+            }
+        }
+    }
+
+    file.seek(SeekFrom::Start(offset))
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let mut reader = BufReader::new(file);
+    let mut buf = Vec::new();
+    let mut unterminated = None;
+
+    loop {
+        buf.clear();
+        let read = reader.read_until(b'\n', &mut buf)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        if buf.last() != Some(&b'\n') {
+            unterminated = Some(buf.clone());
+            break;
+        }
+        offset += read as u64;
+        hash = fnv1a(hash, &buf);
+        scan_bytes(&buf, config, verbose, &mut stats, &mut leaders);
+        if leaders.len() > top.saturating_mul(2).max(64) {
+            rank(&mut leaders, top);
+        }
+    }
+    rank(&mut leaders, top);
+
+    let checkpoint = Checkpoint {
+        version: VERSION,
+        settings,
+        offset,
+        prefix_hash: hash,
+        top,
+        statistics: stats,
+        leaders,
+    };
+    checkpoint.save(state_path)?;
+
+    let Checkpoint { mut statistics, mut leaders, .. } = checkpoint;
+    if let Some(line) = unterminated {
+        scan_bytes(&line, config, verbose, &mut statistics, &mut leaders);
+        rank(&mut leaders, top);
+    }
+    Ok((leaders, statistics))
+}
+
+/// Scans one raw line the way `scan_file` does, collecting rankable missions.
+fn scan_bytes(raw: &[u8], config: &Config, verbose: bool, stats: &mut Statistics, leaders: &mut Vec<Mission>) {
+    stats.total_lines += 1;
+    let line_number = stats.total_lines;
+
+    let mut raw = raw.strip_suffix(b"\n").unwrap_or(raw);
+    raw = raw.strip_suffix(b"\r").unwrap_or(raw);
+    let line = match std::str::from_utf8(raw) {
+        Ok(l) => l,
+        Err(e) => return count_read_error(line_number, &e, verbose, stats),
+    };
+
+    scan_line(line, line_number, config, verbose, stats, &mut |mission, rejection| {
+        if rejection.is_none() {
+            leaders.push(mission);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn mission_line(id: &str, duration: u32) -> String {
+        format!("2045-01-01 | {} | Mars | Completed | 3 | {} | 90.0 | ABC-123-XYZ\n", id, duration)
+    }
+
+    fn append(path: &Path, text: &str) {
+        let mut file = fs::OpenOptions::new().append(true).create(true).open(path).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    fn ids(missions: &[Mission]) -> Vec<&str> {
+        missions.iter().map(|m| m.mission_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_resume_matches_full_scan() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = dir.path().join("missions.log");
+        let state = dir.path().join("state.json");
+        let config = Config::default();
+
+        append(&log, &mission_line("AAA-0001", 300));
+        append(&log, &mission_line("BBB-0002", 100));
+        let (leaders, stats) = scan_incremental(&log, &state, &config, 0, 2, false).unwrap();
+        assert_eq!(ids(&leaders), vec!["AAA-0001", "BBB-0002"]);
+        assert_eq!(stats.total_lines, 2);
+
+        let partial = "2045-01-01 | DDD-0004 | Mars | Completed | 3 | 900";
+        append(&log, &mission_line("CCC-0003", 200));
+        append(&log, partial);
+        let (leaders, stats) = scan_incremental(&log, &state, &config, 0, 2, false).unwrap();
+        assert_eq!(ids(&leaders), vec!["AAA-0001", "CCC-0003"]);
+        assert_eq!(stats.total_lines, 4);
+        assert_eq!(stats.rejections.invalid_format, 1);
+
+        // The unterminated line was ranked but not checkpointed
+        let saved = Checkpoint::load(&state).unwrap().unwrap();
+        assert_eq!(saved.statistics.total_lines, 3);
+        assert_eq!(saved.offset, fs::metadata(&log).unwrap().len() - partial.len() as u64);
+    }
+
+    #[test]
+    fn test_rewritten_log_is_rescanned() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = dir.path().join("missions.log");
+        let state = dir.path().join("state.json");
+        let config = Config::default();
+
+        append(&log, &mission_line("AAA-0001", 300));
+        scan_incremental(&log, &state, &config, 0, 1, false).unwrap();
+
+        fs::write(&log, mission_line("ZZZ-0001", 100) + &mission_line("ZZZ-0002", 50)).unwrap();
+        let (leaders, stats) = scan_incremental(&log, &state, &config, 0, 1, false).unwrap();
+        assert_eq!(ids(&leaders), vec!["ZZZ-0001"]);
+        assert_eq!(stats.total_lines, 2);
+    }
+
+    #[test]
+    fn test_edit_in_middle_of_long_prefix_is_rescanned() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = dir.path().join("missions.log");
+        let state = dir.path().join("state.json");
+        let config = Config::default();
+
+        let filler: String = (0..4000).map(|i| mission_line(&format!("F{:03}-0001", i % 1000), 10)).collect();
+        fs::write(&log, &filler).unwrap();
+        append(&log, &mission_line("AAA-0001", 300));
+        scan_incremental(&log, &state, &config, 0, 1, false).unwrap();
+
+        // Same length, far from either end of the scanned part
+        let edited = filler.replacen("| 10 |", "| 99 |", 2000);
+        let edited = edited.replacen("| 99 |", "| 10 |", 1999);
+        fs::write(&log, edited + &mission_line("AAA-0001", 300)).unwrap();
+        append(&log, &mission_line("BBB-0002", 200));
+
+        let checkpoint = Checkpoint::load(&state).unwrap().unwrap();
+        let mut file = File::open(&log).unwrap();
+        assert_eq!(
+            checkpoint.stale_reason(&mut file, 0, 1).unwrap(),
+            Some("the scanned part of the log was rewritten")
+        );
+    }
+
+    #[test]
+    fn test_stale_reasons() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = dir.path().join("missions.log");
+        let state = dir.path().join("state.json");
+        append(&log, &mission_line("AAA-0001", 300));
+        scan_incremental(&log, &state, &Config::default(), 7, 1, false).unwrap();
+
+        let checkpoint = Checkpoint::load(&state).unwrap().unwrap();
+        let mut file = File::open(&log).unwrap();
+        assert_eq!(checkpoint.stale_reason(&mut file, 7, 1).unwrap(), None);
+        assert_eq!(checkpoint.stale_reason(&mut file, 8, 1).unwrap(), Some("--config or --rules changed"));
+        assert_eq!(checkpoint.stale_reason(&mut file, 7, 5).unwrap(), Some("--top is larger than the checkpoint keeps"));
+    }
+}
@@ -0,0 +1,243 @@
+use crate::tabular::Cell;
+use crate::Mission;
+use arrow_array::{
+    ArrayRef, Date32Array, Float64Array, Int64Array, RecordBatch, StringArray, UInt32Array,
+    UInt64Array,
+};
+use arrow_schema::{DataType, Field, Schema};
+use chrono::NaiveDate;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::io::Write;
+use std::sync::Arc;
+
+fn schema() -> Schema {
+    Schema::new(vec![
+        Field::new("rank", DataType::UInt32, false),
+        // Dates that don't parse as YYYY-MM-DD are written as null
+        Field::new("date", DataType::Date32, true),
+        Field::new("mission_id", DataType::Utf8, false),
+        Field::new("destination", DataType::Utf8, false),
+        Field::new("status", DataType::Utf8, false),
+        Field::new("crew_size", DataType::UInt32, false),
+        Field::new("duration_days", DataType::UInt32, false),
+        Field::new("success_rate", DataType::Float64, false),
+        Field::new("security_code", DataType::Utf8, false),
+        Field::new("line_number", DataType::UInt64, false),
+    ])
+}
+
+/// Days since the Unix epoch, as stored by Arrow's `Date32`.
+fn date32(date: &str) -> Option<i32> {
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1)?;
+    let parsed = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    i32::try_from((parsed - epoch).num_days()).ok()
+}
+
+/// Builds a single record batch holding the ranked missions.
+fn record_batch(missions: &[Mission]) -> Result<RecordBatch, String> {
+    let outputs: Vec<_> = missions
+        .iter()
+        .enumerate()
+        .map(|(idx, m)| m.to_output(idx + 1))
+        .collect();
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(UInt32Array::from_iter_values(outputs.iter().map(|m| m.rank as u32))),
+        Arc::new(Date32Array::from_iter(outputs.iter().map(|m| date32(&m.date)))),
+        Arc::new(StringArray::from_iter_values(outputs.iter().map(|m| m.mission_id.as_str()))),
+        Arc::new(StringArray::from_iter_values(outputs.iter().map(|m| m.destination.as_str()))),
+        Arc::new(StringArray::from_iter_values(outputs.iter().map(|m| m.status.as_str()))),
+        Arc::new(UInt32Array::from_iter_values(outputs.iter().map(|m| m.crew_size))),
+        Arc::new(UInt32Array::from_iter_values(outputs.iter().map(|m| m.duration_days))),
+        Arc::new(Float64Array::from_iter_values(outputs.iter().map(|m| m.success_rate))),
+        Arc::new(StringArray::from_iter_values(outputs.iter().map(|m| m.security_code.as_str()))),
+        Arc::new(UInt64Array::from_iter_values(outputs.iter().map(|m| m.line_number as u64))),
+    ];
+
+    RecordBatch::try_new(Arc::new(schema()), columns)
+        .map_err(|e| format!("Failed to build record batch: {}", e))
+}
+
+/// Builds a record batch from generic rows. Each column takes its type
+/// from its first non-null cell (text if there is none); a cell of any
+/// other type is an error rather than being dropped.
+fn rows_batch(headers: &[&str], rows: &[Vec<Cell>]) -> Result<RecordBatch, String> {
+    let mut fields = Vec::with_capacity(headers.len());
+    let mut columns: Vec<ArrayRef> = Vec::with_capacity(headers.len());
+
+    for (idx, name) in headers.iter().enumerate() {
+        let cells = || rows.iter().map(move |r| &r[idx]);
+        let first = cells().find(|c| **c != Cell::Null);
+        let mismatch = |cell: &Cell| {
+            format!("Column '{}' mixes {} and {} values", name, kind(first.unwrap_or(cell)), kind(cell))
+        };
+
+        let (data_type, column): (DataType, ArrayRef) = match first {
+            Some(Cell::Int(_)) => (
+                DataType::Int64,
+                Arc::new(cells()
+                    .map(|c| match c {
+                        Cell::Int(i) => Ok(Some(*i)),
+                        Cell::Null => Ok(None),
+                        other => Err(mismatch(other)),
+                    })
+                    .collect::<Result<Int64Array, String>>()?),
+            ),
+            Some(Cell::Float(_)) => (
+                DataType::Float64,
+                Arc::new(cells()
+                    .map(|c| match c {
+                        Cell::Float(f) => Ok(Some(*f)),
+                        Cell::Null => Ok(None),
+                        other => Err(mismatch(other)),
+                    })
+                    .collect::<Result<Float64Array, String>>()?),
+            ),
+            _ => (
+                DataType::Utf8,
+                Arc::new(cells()
+                    .map(|c| match c {
+                        Cell::Text(s) => Ok(Some(s.as_str())),
+                        Cell::Null => Ok(None),
+                        other => Err(mismatch(other)),
+                    })
+                    .collect::<Result<StringArray, String>>()?),
+            ),
+        };
+        fields.push(Field::new(*name, data_type, true));
+        columns.push(column);
+    }
+
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+        .map_err(|e| format!("Failed to build record batch: {}", e))
+}
+
+fn kind(cell: &Cell) -> &'static str {
+    match cell {
+        Cell::Text(_) => "text",
+        Cell::Int(_) => "integer",
+        Cell::Float(_) => "float",
+        Cell::Null => "null",
+    }
+}
+
+/// Writes the ranked missions as a Snappy-compressed Parquet file.
+pub fn write_parquet<W: Write + Send>(out: W, missions: &[Mission]) -> Result<(), String> {
+    write_parquet_batch(out, &record_batch(missions)?)
+}
+
+/// Writes the ranked missions in the Arrow IPC file format (Feather v2).
+pub fn write_arrow<W: Write>(out: W, missions: &[Mission]) -> Result<(), String> {
+    write_arrow_batch(out, &record_batch(missions)?)
+}
+
+pub fn write_rows_parquet<W: Write + Send>(out: W, headers: &[&str], rows: &[Vec<Cell>]) -> Result<(), String> {
+    write_parquet_batch(out, &rows_batch(headers, rows)?)
+}
+
+pub fn write_rows_arrow<W: Write>(out: W, headers: &[&str], rows: &[Vec<Cell>]) -> Result<(), String> {
+    write_arrow_batch(out, &rows_batch(headers, rows)?)
+}
+
+fn write_parquet_batch<W: Write + Send>(out: W, batch: &RecordBatch) -> Result<(), String> {
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+
+    let mut writer = ArrowWriter::try_new(out, batch.schema(), Some(props))
+        .map_err(|e| format!("Failed to create Parquet writer: {}", e))?;
+    writer
+        .write(batch)
+        .map_err(|e| format!("Failed to write Parquet data: {}", e))?;
+    writer
+        .close()
+        .map_err(|e| format!("Failed to finish Parquet file: {}", e))?;
+
+    Ok(())
+}
+
+fn write_arrow_batch<W: Write>(out: W, batch: &RecordBatch) -> Result<(), String> {
+    let mut writer = arrow_ipc::writer::FileWriter::try_new(out, &batch.schema())
+        .map_err(|e| format!("Failed to create Arrow writer: {}", e))?;
+    writer
+        .write(batch)
+        .map_err(|e| format!("Failed to write Arrow data: {}", e))?;
+    writer
+        .finish()
+        .map_err(|e| format!("Failed to finish Arrow file: {}", e))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Array;
+
+    fn missions() -> Vec<Mission> {
+        vec![
+            Mission::from_line("2045-07-12 | M1 | Mars | Completed | 5 | 500 | 98.7 | TRX-842-YHG", 3).unwrap(),
+            Mission::from_line("not-a-date | M2 | Mars | Completed | 4 | 300 | 91.0 | ABC-123-XYZ", 7).unwrap(),
+        ]
+    }
+
+    #[test]
+    fn test_date32() {
+        assert_eq!(date32("1970-01-02"), Some(1));
+        assert_eq!(date32("2045-07-12"), Some(27586));
+        assert_eq!(date32("2045-13-01"), None);
+    }
+
+    #[test]
+    fn test_record_batch_types() {
+        let batch = record_batch(&missions()).unwrap();
+
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.schema().field_with_name("date").unwrap().data_type(), &DataType::Date32);
+        assert_eq!(batch.schema().field_with_name("crew_size").unwrap().data_type(), &DataType::UInt32);
+        assert!(batch.column(1).is_null(1), "Unparseable dates become null");
+    }
+
+    #[test]
+    fn test_rows_batch_types() {
+        let rows = vec![
+            vec![Cell::text("Mars"), Cell::Int(3), Cell::Null],
+            vec![Cell::text("Moon"), Cell::Null, Cell::Float(0.5)],
+        ];
+        let batch = rows_batch(&["group", "count", "mean"], &rows).unwrap();
+
+        assert_eq!(batch.schema().field(0).data_type(), &DataType::Utf8);
+        assert_eq!(batch.schema().field(1).data_type(), &DataType::Int64);
+        assert_eq!(batch.schema().field(2).data_type(), &DataType::Float64, "Typed by the first non-null cell");
+        assert!(batch.column(2).is_null(0));
+        assert!(!batch.column(2).is_null(1));
+    }
+
+    #[test]
+    fn test_rows_batch_rejects_mixed_types() {
+        let rows = vec![vec![Cell::Int(3)], vec![Cell::Float(2.0)]];
+        let err = rows_batch(&["count"], &rows).unwrap_err();
+        assert_eq!(err, "Column 'count' mixes integer and float values");
+    }
+
+    #[test]
+    fn test_arrow_round_trip() {
+        let mut buf = Vec::new();
+        write_arrow(&mut buf, &missions()).unwrap();
+
+        let reader = arrow_ipc::reader::FileReader::try_new(std::io::Cursor::new(buf), None).unwrap();
+        let batches: Vec<RecordBatch> = reader.map(|b| b.unwrap()).collect();
+        assert_eq!(batches[0].num_rows(), 2);
+    }
+
+    #[test]
+    fn test_write_parquet_magic() {
+        let mut buf = Vec::new();
+        write_parquet(&mut buf, &missions()).unwrap();
+
+        assert_eq!(&buf[..4], b"PAR1");
+        assert_eq!(&buf[buf.len() - 4..], b"PAR1");
+    }
+}
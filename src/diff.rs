@@ -0,0 +1,283 @@
+use crate::rules::Field;
+use crate::tabular::Cell;
+use crate::validation::Config;
+use crate::{scan_file, Mission};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Fields compared between the two versions of a mission.
+const COMPARED: [Field; 7] = [
+    Field::Date,
+    Field::Destination,
+    Field::Status,
+    Field::CrewSize,
+    Field::Duration,
+    Field::SuccessRate,
+    Field::SecurityCode,
+];
+
+/// Every parsed mission of one log plus its ranking order.
+pub struct Snapshot {
+    missions: Vec<Mission>,
+    ranking: Vec<String>,
+}
+
+impl Snapshot {
+    pub fn load(file_path: &PathBuf, config: &Config) -> Result<Self, String> {
+        let mut missions = Vec::new();
+        let mut valid = Vec::new();
+        scan_file(file_path, config, false, |mission, rejection| {
+            if rejection.is_none() {
+                valid.push(mission.clone());
+            }
+            missions.push(mission);
+        })?;
+
+        valid.sort_by_key(|m| Reverse(m.duration));
+        Ok(Snapshot { missions, ranking: valid.into_iter().map(|m| m.mission_id).collect() })
+    }
+
+    /// Missions keyed by ID; a repeated ID keeps its last occurrence.
+    fn by_id(&self) -> HashMap<&str, &Mission> {
+        self.missions.iter().map(|m| (m.mission_id.as_str(), m)).collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub old: String,
+    pub new: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Modified {
+    pub mission_id: String,
+    pub old_line: usize,
+    pub new_line: usize,
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RankingChange {
+    pub top: usize,
+    pub changed: bool,
+    pub old: Vec<String>,
+    pub new: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogDiff {
+    pub added: Vec<Mission>,
+    pub removed: Vec<Mission>,
+    pub modified: Vec<Modified>,
+    pub ranking: RankingChange,
+}
+
+impl LogDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Compares two logs keyed on mission ID. Results follow line order: the
+/// new log for added and modified missions, the old log for removed ones.
+pub fn compare(old: &Snapshot, new: &Snapshot, top: usize) -> LogDiff {
+    let old_ids = old.by_id();
+    let new_ids = new.by_id();
+
+    let mut added: Vec<Mission> = Vec::new();
+    let mut modified = Vec::new();
+    for (id, mission) in sorted_by_line(&new_ids) {
+        match old_ids.get(id) {
+            None => added.push(mission.clone()),
+            Some(before) => {
+                let changes: Vec<FieldChange> = COMPARED
+                    .iter()
+                    .filter_map(|field| {
+                        let (a, b) = (field.text(before), field.text(mission));
+                        (a != b).then(|| FieldChange { field: field.as_str(), old: a, new: b })
+                    })
+                    .collect();
+                if !changes.is_empty() {
+                    modified.push(Modified {
+                        mission_id: id.to_string(),
+                        old_line: before.line_number,
+                        new_line: mission.line_number,
+                        changes,
+                    });
+                }
+            }
+        }
+    }
+
+    let removed = sorted_by_line(&old_ids)
+        .into_iter()
+        .filter(|(id, _)| !new_ids.contains_key(id))
+        .map(|(_, m)| m.clone())
+        .collect();
+
+    let old_top: Vec<String> = old.ranking.iter().take(top).cloned().collect();
+    let new_top: Vec<String> = new.ranking.iter().take(top).cloned().collect();
+
+    LogDiff {
+        added,
+        removed,
+        modified,
+        ranking: RankingChange { top, changed: old_top != new_top, old: old_top, new: new_top },
+    }
+}
+
+fn sorted_by_line<'a>(ids: &HashMap<&'a str, &'a Mission>) -> Vec<(&'a str, &'a Mission)> {
+    let mut entries: Vec<(&str, &Mission)> = ids.iter().map(|(id, m)| (*id, *m)).collect();
+    entries.sort_by_key(|(_, m)| m.line_number);
+    entries
+}
+
+pub fn render_text(diff: &LogDiff) -> String {
+    let mut out = String::new();
+
+    if diff.is_empty() {
+        out.push_str("No mission changes.\n");
+    }
+    if !diff.added.is_empty() {
+        out.push_str(&format!("Added ({}):\n", diff.added.len()));
+        for m in &diff.added {
+            out.push_str(&format!("  + {} (line {})\n", m.mission_id, m.line_number));
+        }
+    }
+    if !diff.removed.is_empty() {
+        out.push_str(&format!("Removed ({}):\n", diff.removed.len()));
+        for m in &diff.removed {
+            out.push_str(&format!("  - {} (line {})\n", m.mission_id, m.line_number));
+        }
+    }
+    if !diff.modified.is_empty() {
+        out.push_str(&format!("Modified ({}):\n", diff.modified.len()));
+        for m in &diff.modified {
+            let changes: Vec<String> = m
+                .changes
+                .iter()
+                .map(|c| format!("{} {} -> {}", c.field, c.old, c.new))
+                .collect();
+            out.push_str(&format!("  ~ {}: {}\n", m.mission_id, changes.join(", ")));
+        }
+    }
+
+    let ranking = &diff.ranking;
+    if ranking.changed {
+        out.push_str(&format!("Top {} ranking changed:\n", ranking.top));
+        out.push_str(&format!("  old: {}\n", ranking.old.join(", ")));
+        out.push_str(&format!("  new: {}\n", ranking.new.join(", ")));
+    } else {
+        out.push_str(&format!("Top {} ranking unchanged: {}\n", ranking.top, ranking.new.join(", ")));
+    }
+
+    out
+}
+
+/// One row per added or removed mission and per changed field.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChangeRecord {
+    pub change: &'static str,
+    pub mission_id: String,
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+pub fn records(diff: &LogDiff) -> Vec<ChangeRecord> {
+    let whole = |change, m: &Mission| ChangeRecord {
+        change,
+        mission_id: m.mission_id.clone(),
+        field: String::new(),
+        old: String::new(),
+        new: String::new(),
+    };
+
+    let mut records: Vec<ChangeRecord> = diff.added.iter().map(|m| whole("added", m)).collect();
+    records.extend(diff.removed.iter().map(|m| whole("removed", m)));
+    for m in &diff.modified {
+        records.extend(m.changes.iter().map(|c| ChangeRecord {
+            change: "modified",
+            mission_id: m.mission_id.clone(),
+            field: c.field.to_string(),
+            old: c.old.clone(),
+            new: c.new.clone(),
+        }));
+    }
+    records
+}
+
+pub const HEADERS: [&str; 5] = ["Change", "Mission ID", "Field", "Old", "New"];
+
+pub fn rows(records: &[ChangeRecord]) -> Vec<Vec<Cell>> {
+    records
+        .iter()
+        .map(|r| {
+            vec![
+                Cell::text(r.change),
+                Cell::text(r.mission_id.as_str()),
+                Cell::text(r.field.as_str()),
+                Cell::text(r.old.as_str()),
+                Cell::text(r.new.as_str()),
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(lines: &[&str]) -> Snapshot {
+        let missions: Vec<Mission> = lines
+            .iter()
+            .enumerate()
+            .map(|(idx, l)| Mission::from_line(l, idx + 1).unwrap())
+            .collect();
+        let mut valid: Vec<&Mission> = missions.iter().filter(|m| m.destination == "Mars").collect();
+        valid.sort_by_key(|m| Reverse(m.duration));
+        let ranking = valid.iter().map(|m| m.mission_id.clone()).collect();
+        Snapshot { missions, ranking }
+    }
+
+    #[test]
+    fn test_compare() {
+        let old = snapshot(&[
+            "2045-01-01 | A | Mars | Completed | 3 | 500 | 90.0 | ABC-123-XYZ",
+            "2045-01-02 | B | Mars | Completed | 3 | 400 | 90.0 | ABC-123-XYZ",
+            "2045-01-03 | C | Mars | Completed | 3 | 300 | 90.0 | ABC-123-XYZ",
+        ]);
+        let new = snapshot(&[
+            "2045-01-01 | A | Mars | Completed | 3 | 500 | 90.0 | ABC-123-XYZ",
+            "2045-01-03 | C | Mars | Completed | 4 | 600 | 90.0 | ABC-123-XYZ",
+            "2045-01-04 | D | Mars | Completed | 3 | 100 | 90.0 | ABC-123-XYZ",
+        ]);
+        let diff = compare(&old, &new, 2);
+
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].mission_id, "D");
+        assert_eq!(diff.removed[0].mission_id, "B");
+        assert_eq!(diff.modified.len(), 1);
+        assert_eq!(diff.modified[0].changes, vec![
+            FieldChange { field: "crew_size", old: "3".into(), new: "4".into() },
+            FieldChange { field: "duration", old: "300".into(), new: "600".into() },
+        ]);
+        assert!(diff.ranking.changed);
+        assert_eq!(diff.ranking.new, vec!["C", "A"]);
+        assert_eq!(records(&diff).len(), 4);
+    }
+
+    #[test]
+    fn test_identical_logs() {
+        let lines = ["2045-01-01 | A | Mars | Completed | 3 | 500 | 90.0 | ABC-123-XYZ"];
+        let diff = compare(&snapshot(&lines), &snapshot(&lines), 1);
+
+        assert!(diff.is_empty());
+        assert!(!diff.ranking.changed);
+        assert_eq!(render_text(&diff), "No mission changes.\nTop 1 ranking unchanged: A\n");
+    }
+}
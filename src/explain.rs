@@ -0,0 +1,262 @@
+use crate::aggregate::GroupKey;
+use crate::rules::Severity;
+use crate::tabular::Cell;
+use crate::validation::Config;
+use crate::{is_comment_or_metadata, lint, Mission, Rejection};
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    Pass,
+    Fail,
+    /// An earlier stage already decided the outcome.
+    Skipped,
+}
+
+impl Verdict {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Verdict::Pass => "pass",
+            Verdict::Fail => "fail",
+            Verdict::Skipped => "skipped",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Step {
+    pub stage: &'static str,
+    pub verdict: Verdict,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Explanation {
+    pub line_number: usize,
+    pub source: String,
+    pub included: bool,
+    pub steps: Vec<Step>,
+}
+
+/// What the ranking run decided, for tracing individual lines through it.
+pub struct Context<'a> {
+    pub config: &'a Config,
+    /// Every parsed mission with the reason the scan rejected it, if any.
+    pub records: &'a [(Mission, Option<Rejection>)],
+    /// Missions dropped after scanning by `--dedupe`, `--final-state`, or
+    /// `--exclude-outliers`, with the reason.
+    pub filtered: &'a HashMap<usize, String>,
+    /// The final ranking, longest first.
+    pub ranked: &'a [Mission],
+    pub top: usize,
+    pub group_by: Option<GroupKey>,
+}
+
+/// Scan checks in the order `scan_file` applies them.
+const CHECKS: [&str; 5] = ["rules", "destination", "status", "mission id", "ranges"];
+
+fn check_index(rejection: Rejection) -> usize {
+    match rejection {
+        Rejection::ZeroDuration | Rejection::InvalidSecurityCode | Rejection::RuleViolation => 0,
+        Rejection::NotMars => 1,
+        Rejection::NotCompleted => 2,
+        Rejection::InvalidMissionId => 3,
+        Rejection::CrewSizeOutOfRange
+        | Rejection::DurationOutOfRange
+        | Rejection::SuccessRateOutOfRange => 4,
+    }
+}
+
+fn check_detail(index: usize, mission: &Mission, config: &Config) -> String {
+    match index {
+        0 => {
+            let errors: Vec<String> = config
+                .rules
+                .check(mission)
+                .into_iter()
+                .filter(|v| v.severity == Severity::Error)
+                .map(|v| format!("{}: {}", v.rule, v.message))
+                .collect();
+            if errors.is_empty() { "no error rules violated".to_string() } else { errors.join("; ") }
+        }
+        1 => mission.destination.clone(),
+        2 => mission.status.clone(),
+        3 => mission.mission_id.clone(),
+        _ => match config.ranges.violation(mission) {
+            Some(v) => format!("{} {} out of range ({})", v.field, v.value, v.range),
+            None => "crew size, duration, and success rate in range".to_string(),
+        },
+    }
+}
+
+fn step(stage: &'static str, verdict: Verdict, detail: impl Into<String>) -> Step {
+    Step { stage, verdict, detail: detail.into() }
+}
+
+/// Traces line `line_number` (whose text is `source`) through every stage
+/// of the ranking run described by `ctx`.
+pub fn explain_line(line_number: usize, source: &str, ctx: &Context) -> Explanation {
+    let mut steps = Vec::new();
+    let mut failed = false;
+
+    let mut push = |stage, pass: bool, detail: String| {
+        let verdict = if failed {
+            Verdict::Skipped
+        } else if pass {
+            Verdict::Pass
+        } else {
+            failed = true;
+            Verdict::Fail
+        };
+        let detail = if verdict == Verdict::Skipped { String::new() } else { detail };
+        steps.push(step(stage, verdict, detail));
+    };
+
+    let metadata = is_comment_or_metadata(source);
+    push("metadata", !metadata, if metadata { "comment or metadata line".into() } else { "data line".into() });
+
+    let record = ctx.records.iter().find(|(m, _)| m.line_number == line_number);
+    let parse_detail = match record {
+        Some((m, _)) => format!("mission {}", m.mission_id),
+        None => lint::format_problem(source),
+    };
+    push("parse", record.is_some(), parse_detail);
+
+    let ranked_position = ctx.ranked.iter().position(|m| m.line_number == line_number);
+
+    match record {
+        Some((mission, rejection)) => {
+            // --group-by destination ranks successful missions anywhere
+            let kept_anyway = *rejection == Some(Rejection::NotMars) && ctx.group_by == Some(GroupKey::Destination)
+                && (ranked_position.is_some() || ctx.filtered.contains_key(&line_number));
+            let failed_at = rejection.filter(|_| !kept_anyway).map(check_index);
+
+            for (idx, stage) in CHECKS.iter().enumerate() {
+                let mut detail = check_detail(idx, mission, ctx.config);
+                if idx == 1 && kept_anyway {
+                    detail.push_str(" (all destinations ranked by --group-by destination)");
+                }
+                push(stage, failed_at != Some(idx), detail);
+            }
+
+            let dropped = ctx.filtered.get(&line_number);
+            push("filters", dropped.is_none(), dropped.cloned().unwrap_or_else(|| "kept".to_string()));
+
+            let (position, total) = match ctx.group_by {
+                Some(key) => {
+                    let group = key.key_of(mission);
+                    let members: Vec<&Mission> = ctx.ranked.iter().filter(|m| key.key_of(m) == group).collect();
+                    (members.iter().position(|m| m.line_number == line_number), members.len())
+                }
+                None => (ranked_position, ctx.ranked.len()),
+            };
+            let (shown, detail) = match position {
+                Some(p) if p < ctx.top => (true, format!("rank {} of {}", p + 1, total)),
+                Some(p) => (false, format!("rank {} of {}, outside --top {}", p + 1, total, ctx.top)),
+                None => (false, "not ranked".to_string()),
+            };
+            push("ranking", shown, detail);
+        }
+        None => {
+            for stage in CHECKS.iter().chain(&["filters", "ranking"]) {
+                push(stage, false, String::new());
+            }
+        }
+    }
+
+    let included = steps.iter().all(|s| s.verdict == Verdict::Pass);
+    Explanation { line_number, source: source.to_string(), included, steps }
+}
+
+pub fn render_text(explanations: &[Explanation]) -> String {
+    let blocks: Vec<String> = explanations
+        .iter()
+        .map(|e| {
+            let mut out = format!("Line {}: {}\n", e.line_number, e.source);
+            for s in &e.steps {
+                let line = format!("  {:<14} {:<8} {}", s.stage, s.verdict.as_str(), s.detail);
+                out.push_str(line.trim_end());
+                out.push('\n');
+            }
+            let verdict = match e.steps.iter().find(|s| s.verdict == Verdict::Fail) {
+                None => "included".to_string(),
+                Some(s) => format!("excluded at {}", s.stage),
+            };
+            out.push_str(&format!("Verdict: {}\n", verdict));
+            out
+        })
+        .collect();
+    blocks.join("\n")
+}
+
+pub const HEADERS: [&str; 4] = ["Line", "Stage", "Verdict", "Detail"];
+
+pub fn rows(explanations: &[Explanation]) -> Vec<Vec<Cell>> {
+    explanations
+        .iter()
+        .flat_map(|e| {
+            e.steps.iter().map(move |s| {
+                vec![
+                    Cell::Int(e.line_number as i64),
+                    Cell::text(s.stage),
+                    Cell::text(s.verdict.as_str()),
+                    Cell::text(s.detail.as_str()),
+                ]
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mission(line: &str, line_number: usize) -> Mission {
+        Mission::from_line(line, line_number).unwrap()
+    }
+
+    #[test]
+    fn test_explain_rejected_and_ranked_lines() {
+        let config = Config::default();
+        let winner = mission("2045-01-01 | KLM-0001 | Mars | Completed | 3 | 900 | 90.0 | ABC-123-XYZ", 2);
+        let runner_up = mission("2045-01-01 | KLM-0002 | Mars | Completed | 3 | 500 | 90.0 | ABC-123-XYZ", 3);
+        let failed = mission("2045-01-01 | KLM-0003 | Mars | Failed | 3 | 999 | 90.0 | ABC-123-XYZ", 4);
+        let records = vec![
+            (winner.clone(), None),
+            (runner_up.clone(), None),
+            (failed.clone(), Some(Rejection::NotCompleted)),
+        ];
+        let ranked = vec![winner, runner_up];
+        let filtered = HashMap::new();
+        let ctx = Context { config: &config, records: &records, filtered: &filtered, ranked: &ranked, top: 1, group_by: None };
+
+        let e = explain_line(2, "data", &ctx);
+        assert!(e.included);
+
+        let e = explain_line(3, "data", &ctx);
+        assert!(!e.included);
+        assert_eq!(e.steps.last().unwrap().detail, "rank 2 of 2, outside --top 1");
+
+        let e = explain_line(4, "data", &ctx);
+        let verdicts: Vec<&str> = e.steps.iter().map(|s| s.verdict.as_str()).collect();
+        assert_eq!(verdicts[..6], ["pass", "pass", "pass", "pass", "fail", "skipped"]);
+        assert_eq!(e.steps[4].detail, "Failed");
+    }
+
+    #[test]
+    fn test_explain_metadata_and_parse_failures() {
+        let config = Config::default();
+        let filtered = HashMap::new();
+        let ctx = Context { config: &config, records: &[], filtered: &filtered, ranked: &[], top: 1, group_by: None };
+
+        let e = explain_line(1, "# comment", &ctx);
+        assert_eq!(e.steps[0].verdict, Verdict::Fail);
+        assert!(e.steps[1..].iter().all(|s| s.verdict == Verdict::Skipped));
+
+        let out = render_text(&[explain_line(2, "a | b", &ctx)]);
+        assert!(out.contains("  parse          fail     expected 8 '|'-separated fields, found 2\n"));
+        assert!(out.ends_with("Verdict: excluded at parse\n"));
+    }
+}
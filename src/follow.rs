@@ -0,0 +1,229 @@
+use std::fs::{self, File, Metadata};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long `--follow` waits between checks for new data.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Most bytes one poll reads, so a large existing file is taken in pieces
+/// rather than loaded whole.
+const MAX_READ: u64 = 1024 * 1024;
+
+/// Identifies the file behind a path, so rotation can be told apart from
+/// appends. Only available on Unix; elsewhere only truncation is detected.
+#[cfg(unix)]
+fn identity(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn identity(_metadata: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// What changed since the last poll.
+#[derive(Debug, Default, PartialEq)]
+pub struct Batch {
+    /// The file shrank, so everything read so far is gone.
+    pub truncated: bool,
+    /// A new file replaced the old one at the same path.
+    pub rotated: bool,
+    /// Complete new lines with their 1-based line numbers.
+    pub lines: Vec<(usize, String)>,
+    /// The read stopped at the per-poll limit; poll again without waiting.
+    pub more: bool,
+}
+
+/// Reads a growing file like `tail -F`: it keeps the current position,
+/// holds back a trailing partial line until its newline arrives, and reopens
+/// the path when the file is rotated or truncated.
+pub struct Follower {
+    path: PathBuf,
+    file: Option<File>,
+    identity: Option<(u64, u64)>,
+    offset: u64,
+    line_number: usize,
+    partial: Vec<u8>,
+}
+
+impl Follower {
+    pub fn new(path: &Path) -> Self {
+        Follower {
+            path: path.to_path_buf(),
+            file: None,
+            identity: None,
+            offset: 0,
+            line_number: 0,
+            partial: Vec::new(),
+        }
+    }
+
+    /// Reads whatever was appended since the last call. A missing file is
+    /// not an error, since it may not have been created yet or may be
+    /// between rotation steps.
+    pub fn poll(&mut self) -> io::Result<Batch> {
+        let mut batch = Batch::default();
+
+        let metadata = match fs::metadata(&self.path) {
+            Ok(m) => m,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(batch),
+            Err(e) => return Err(e),
+        };
+
+        if self.file.is_some() && identity(&metadata) != self.identity {
+            // Finish the old file before switching to the new one
+            self.read_new(&mut batch)?;
+            if batch.more {
+                return Ok(batch);
+            }
+            self.flush_partial(&mut batch);
+            self.reopen(&metadata)?;
+            batch.rotated = true;
+        } else if self.file.is_some() && metadata.len() < self.offset {
+            self.reopen(&metadata)?;
+            batch.truncated = true;
+        } else if self.file.is_none() {
+            self.reopen(&metadata)?;
+        }
+
+        self.read_new(&mut batch)?;
+        Ok(batch)
+    }
+
+    fn reopen(&mut self, metadata: &Metadata) -> io::Result<()> {
+        self.file = Some(File::open(&self.path)?);
+        self.identity = identity(metadata);
+        self.offset = 0;
+        self.line_number = 0;
+        self.partial.clear();
+        Ok(())
+    }
+
+    fn read_new(&mut self, batch: &mut Batch) -> io::Result<()> {
+        let file = match self.file.as_mut() {
+            Some(f) => f,
+            None => return Ok(()),
+        };
+
+        file.seek(SeekFrom::Start(self.offset))?;
+        let read = file.take(MAX_READ).read_to_end(&mut self.partial)? as u64;
+        self.offset += read;
+        batch.more = read == MAX_READ;
+
+        while let Some(pos) = self.partial.iter().position(|&b| b == b'\n') {
+            let rest = self.partial.split_off(pos + 1);
+            let mut line = std::mem::replace(&mut self.partial, rest);
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            self.line_number += 1;
+            batch.lines.push((self.line_number, String::from_utf8_lossy(&line).into_owned()));
+        }
+        Ok(())
+    }
+
+    /// A rotated file will never get its final newline, so take the rest.
+    fn flush_partial(&mut self, batch: &mut Batch) {
+        if !self.partial.is_empty() {
+            self.line_number += 1;
+            batch.lines.push((self.line_number, String::from_utf8_lossy(&self.partial).into_owned()));
+            self.partial.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn append(path: &Path, text: &str) {
+        let mut file = fs::OpenOptions::new().append(true).create(true).open(path).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    fn lines(batch: &Batch) -> Vec<(usize, &str)> {
+        batch.lines.iter().map(|(n, l)| (*n, l.as_str())).collect()
+    }
+
+    #[test]
+    fn test_follow_appends_and_partial_lines() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("live.log");
+        append(&path, "one\ntwo\r\nthr");
+
+        let mut follower = Follower::new(&path);
+        assert_eq!(lines(&follower.poll().unwrap()), vec![(1, "one"), (2, "two")]);
+        assert!(follower.poll().unwrap().lines.is_empty());
+
+        append(&path, "ee\nfour\n");
+        assert_eq!(lines(&follower.poll().unwrap()), vec![(3, "three"), (4, "four")]);
+    }
+
+    #[test]
+    fn test_follow_waits_for_missing_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("live.log");
+
+        let mut follower = Follower::new(&path);
+        assert_eq!(follower.poll().unwrap(), Batch::default());
+
+        append(&path, "one\n");
+        assert_eq!(lines(&follower.poll().unwrap()), vec![(1, "one")]);
+    }
+
+    #[test]
+    fn test_follow_truncation() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("live.log");
+        append(&path, "one\ntwo\n");
+
+        let mut follower = Follower::new(&path);
+        follower.poll().unwrap();
+
+        fs::write(&path, "new\n").unwrap();
+        let batch = follower.poll().unwrap();
+        assert!(batch.truncated);
+        assert_eq!(lines(&batch), vec![(1, "new")]);
+    }
+
+    #[test]
+    fn test_follow_reads_large_files_in_pieces() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("live.log");
+        let line = format!("{}\n", "x".repeat(1023));
+        append(&path, &line.repeat(1536));
+
+        let mut follower = Follower::new(&path);
+        let first = follower.poll().unwrap();
+        assert!(first.more);
+        assert_eq!(first.lines.len(), 1024);
+
+        let second = follower.poll().unwrap();
+        assert!(!second.more);
+        assert_eq!(second.lines.len(), 512);
+        assert_eq!(second.lines[0].0, 1025);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_follow_rotation() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("live.log");
+        append(&path, "one\n");
+
+        let mut follower = Follower::new(&path);
+        follower.poll().unwrap();
+
+        append(&path, "last");
+        fs::rename(&path, dir.path().join("live.log.1")).unwrap();
+        append(&path, "fresh\n");
+
+        let batch = follower.poll().unwrap();
+        assert!(batch.rotated);
+        assert_eq!(lines(&batch), vec![(2, "last"), (1, "fresh")]);
+    }
+}
@@ -0,0 +1,77 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Starting state of a 64-bit FNV-1a hash.
+pub const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+
+/// Bytes read at a time while hashing a file.
+const CHUNK: usize = 64 * 1024;
+
+/// 64-bit FNV-1a, continuing from `hash`.
+pub fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Continues `hash` over the first `len` bytes of `file`.
+fn hash_from_start(mut hash: u64, file: &mut File, len: u64) -> io::Result<u64> {
+    file.seek(SeekFrom::Start(0))?;
+    let mut reader = file.take(len);
+    let mut buf = vec![0; CHUNK];
+    loop {
+        let read = reader.read(&mut buf)?;
+        if read == 0 {
+            return Ok(hash);
+        }
+        hash = fnv1a(hash, &buf[..read]);
+    }
+}
+
+/// FNV-1a of the first `len` bytes of `file`. Continuing the result with
+/// `fnv1a` over the bytes that follow gives the hash of the longer prefix.
+pub fn prefix_hash(file: &mut File, len: u64) -> io::Result<u64> {
+    hash_from_start(FNV_OFFSET, file, len)
+}
+
+/// Fingerprints all of `file` by its length and every byte, for caches
+/// that must notice an edit anywhere in it.
+pub fn file_hash(file: &mut File) -> io::Result<u64> {
+    let len = file.metadata()?.len();
+    hash_from_start(fnv1a(FNV_OFFSET, &len.to_le_bytes()), file, len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_prefix_hash_extends_with_fnv1a() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("log");
+        let text = "x".repeat(CHUNK * 2 + 10);
+        fs::write(&path, &text).unwrap();
+        let mut file = File::open(&path).unwrap();
+
+        let head = prefix_hash(&mut file, 100).unwrap();
+        assert_eq!(head, fnv1a(FNV_OFFSET, &text.as_bytes()[..100]));
+        assert_eq!(
+            fnv1a(head, &text.as_bytes()[100..]),
+            prefix_hash(&mut file, text.len() as u64).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_file_hash_sees_same_size_edits() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("log");
+        fs::write(&path, "abc").unwrap();
+        let before = file_hash(&mut File::open(&path).unwrap()).unwrap();
+
+        fs::write(&path, "abd").unwrap();
+        assert_ne!(file_hash(&mut File::open(&path).unwrap()).unwrap(), before);
+    }
+}
@@ -0,0 +1,211 @@
+use crate::tabular::Cell;
+use serde::Serialize;
+
+/// Widest bar drawn when the terminal width is unknown.
+const DEFAULT_BAR_WIDTH: usize = 50;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Bucket {
+    pub lower: u32,
+    pub upper: u32,
+    pub count: usize,
+}
+
+fn fill(mut buckets: Vec<Bucket>, values: &[u32]) -> Vec<Bucket> {
+    for &v in values {
+        // Buckets are sorted and contiguous, so the first upper bound >= v wins
+        let idx = buckets.partition_point(|b| b.upper < v);
+        buckets[idx].count += 1;
+    }
+    buckets
+}
+
+/// Most buckets a histogram may have; a narrower width over a wide range
+/// is an error rather than millions of mostly empty lines.
+pub const MAX_BUCKETS: u64 = 10_000;
+
+fn value_range(values: &[u32]) -> Option<(u64, u64)> {
+    Some((*values.iter().min()? as u64, *values.iter().max()? as u64))
+}
+
+/// Contiguous buckets of `width` (at least 1) values each, from `min` up to
+/// and including `max`.
+fn contiguous(values: &[u32], (min, max): (u64, u64), width: u64) -> Vec<Bucket> {
+    let mut buckets = Vec::new();
+    let mut lower = min;
+    while lower <= max {
+        let upper = (lower + width - 1).min(u32::MAX as u64);
+        buckets.push(Bucket { lower: lower as u32, upper: upper as u32, count: 0 });
+        lower += width;
+    }
+
+    fill(buckets, values)
+}
+
+/// Contiguous buckets of `width` values each, starting at the minimum.
+/// `width` must be at least 1; the CLI rejects 0.
+pub fn equal_width(values: &[u32], width: u32) -> Result<Vec<Bucket>, String> {
+    let Some((min, max)) = value_range(values) else {
+        return Ok(Vec::new());
+    };
+    assert!(width > 0, "bucket width must be at least 1");
+    let width = width as u64;
+
+    let count = (max - min) / width + 1;
+    if count > MAX_BUCKETS {
+        return Err(format!(
+            "A bucket width of {} days makes {} buckets over {}..={}; at most {} are allowed",
+            width, count, min, max, MAX_BUCKETS
+        ));
+    }
+    Ok(contiguous(values, (min, max), width))
+}
+
+/// Splits the value range into at most `count` equal-width buckets.
+pub fn by_bucket_count(values: &[u32], count: usize) -> Vec<Bucket> {
+    let Some((min, max)) = value_range(values) else {
+        return Vec::new();
+    };
+    let span = max - min + 1;
+    let width = span.div_ceil(count.max(1) as u64).max(1);
+
+    contiguous(values, (min, max), width)
+}
+
+/// Buckets bounded by the nearest-rank quantiles, so each holds roughly
+/// `1 / count` of the values. Ties can merge neighbouring buckets.
+pub fn quantiles(values: &[u32], count: usize) -> Vec<Bucket> {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let n = sorted.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let count = count.clamp(1, n);
+
+    let mut uppers: Vec<u32> = (1..=count)
+        .map(|i| sorted[(i * n).div_ceil(count) - 1])
+        .collect();
+    uppers.dedup();
+
+    let mut buckets = Vec::with_capacity(uppers.len());
+    let mut lower = sorted[0];
+    for upper in uppers {
+        buckets.push(Bucket { lower, upper, count: 0 });
+        lower = upper.saturating_add(1);
+    }
+
+    fill(buckets, &sorted)
+}
+
+/// Draws one horizontal bar per bucket, scaled so the fullest bucket fills
+/// the available width.
+pub fn render_ascii(buckets: &[Bucket], max_width: Option<usize>) -> String {
+    let labels: Vec<String> = buckets
+        .iter()
+        .map(|b| format!("{} - {}", b.lower, b.upper))
+        .collect();
+    let label_width = labels.iter().map(|l| l.len()).max().unwrap_or(0);
+    let max_count = buckets.iter().map(|b| b.count).max().unwrap_or(0).max(1);
+    let count_width = max_count.to_string().len();
+
+    let bar_width = match max_width {
+        Some(w) => w.saturating_sub(label_width + count_width + 4).max(10),
+        None => DEFAULT_BAR_WIDTH,
+    };
+
+    let mut out = String::new();
+    for (label, bucket) in labels.iter().zip(buckets) {
+        let len = bucket.count * bar_width / max_count;
+        let bar = if len == 0 && bucket.count > 0 { "▏".to_string() } else { "█".repeat(len) };
+        out.push_str(&format!(
+            "{:>lw$} | {} {}\n",
+            label,
+            bar,
+            bucket.count,
+            lw = label_width
+        ));
+    }
+    out
+}
+
+pub const HEADERS: [&str; 4] = ["Lower", "Upper", "Count", "Percent"];
+
+pub fn rows(buckets: &[Bucket]) -> Vec<Vec<Cell>> {
+    let total: usize = buckets.iter().map(|b| b.count).sum();
+    buckets
+        .iter()
+        .map(|b| {
+            vec![
+                Cell::Int(b.lower as i64),
+                Cell::Int(b.upper as i64),
+                Cell::Int(b.count as i64),
+                Cell::Float(100.0 * b.count as f64 / total.max(1) as f64),
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equal_width() {
+        let buckets = equal_width(&[100, 150, 199, 200, 420], 100).unwrap();
+
+        assert_eq!(buckets.len(), 4);
+        assert_eq!(buckets[0], Bucket { lower: 100, upper: 199, count: 3 });
+        assert_eq!(buckets[1], Bucket { lower: 200, upper: 299, count: 1 });
+        assert_eq!(buckets[3], Bucket { lower: 400, upper: 499, count: 1 });
+        assert!(equal_width(&[], 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_equal_width_limits_bucket_count() {
+        assert!(equal_width(&[1, 4_000_000_000], 1).is_err());
+        assert_eq!(equal_width(&[1, 10_000], 1).unwrap().len(), 10_000);
+    }
+
+    #[test]
+    fn test_by_bucket_count() {
+        let buckets = by_bucket_count(&[100, 150, 199, 200, 1000], 9);
+
+        assert_eq!(buckets.len(), 9);
+        assert_eq!(buckets[0], Bucket { lower: 100, upper: 200, count: 4 });
+        assert_eq!(buckets.iter().map(|b| b.count).sum::<usize>(), 5);
+        assert_eq!(by_bucket_count(&[42, 42], 10), vec![Bucket { lower: 42, upper: 42, count: 2 }]);
+    }
+
+    #[test]
+    fn test_quantiles() {
+        let values: Vec<u32> = (1..=100).collect();
+        let buckets = quantiles(&values, 4);
+
+        assert_eq!(buckets.len(), 4);
+        assert_eq!(buckets[0], Bucket { lower: 1, upper: 25, count: 25 });
+        assert_eq!(buckets[3], Bucket { lower: 76, upper: 100, count: 25 });
+
+        // Heavy ties collapse into a single bucket
+        let tied = quantiles(&[5, 5, 5, 5, 9], 4);
+        assert_eq!(tied, vec![
+            Bucket { lower: 5, upper: 5, count: 4 },
+            Bucket { lower: 6, upper: 9, count: 1 },
+        ]);
+    }
+
+    #[test]
+    fn test_render_ascii() {
+        let buckets = vec![
+            Bucket { lower: 1, upper: 9, count: 4 },
+            Bucket { lower: 10, upper: 19, count: 2 },
+            Bucket { lower: 20, upper: 29, count: 0 },
+        ];
+        let out = render_ascii(&buckets, None);
+        let lines: Vec<&str> = out.lines().collect();
+
+        assert_eq!(lines[0], format!("  1 - 9 | {} 4", "█".repeat(DEFAULT_BAR_WIDTH)));
+        assert_eq!(lines[1], format!("10 - 19 | {} 2", "█".repeat(DEFAULT_BAR_WIDTH / 2)));
+        assert_eq!(lines[2], "20 - 29 |  0");
+    }
+}
@@ -0,0 +1,397 @@
+use crate::hash::file_hash;
+use crate::validation::Config;
+use crate::{check_parsed, count_read_error, is_comment_or_metadata, Mission, Rejection, Statistics};
+use arrow_array::{Array, ArrayRef, Float64Array, RecordBatch, StringArray, UInt32Array, UInt64Array, UInt8Array};
+use arrow_schema::{DataType, Field, Schema};
+use std::collections::HashMap;
+use std::fs::{self, File, Metadata};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+/// Bumped whenever the index layout changes, so old indexes are ignored.
+const VERSION: &str = "1";
+
+const PARSED: u8 = 0;
+const INVALID: u8 = 1;
+const UNREADABLE: u8 = 2;
+
+/// Where the index for `file_path` lives: alongside it, with `.idx` appended.
+pub fn index_path(file_path: &Path) -> PathBuf {
+    let mut path = file_path.as_os_str().to_owned();
+    path.push(".idx");
+    PathBuf::from(path)
+}
+
+/// What a data line held when the log was indexed. Comments and metadata
+/// lines are not stored; they only count towards `total_lines`.
+#[derive(Debug, Clone, PartialEq)]
+enum Entry {
+    Parsed(Mission),
+    Invalid(usize),
+    Unreadable(usize, String),
+}
+
+/// Identifies one version of the source log. The hash covers every byte,
+/// so an edit that keeps the length and modification time is still caught.
+#[derive(Debug, Clone, PartialEq)]
+struct SourceKey {
+    len: u64,
+    mtime_nanos: u128,
+    hash: u64,
+}
+
+impl SourceKey {
+    fn of(file_path: &Path) -> Result<SourceKey, String> {
+        let mut file = File::open(file_path)
+            .map_err(|e| format!("Failed to open file: {}", e))?;
+        let metadata = file.metadata()
+            .map_err(|e| format!("Failed to read file metadata: {}", e))?;
+        let hash = file_hash(&mut file)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        Ok(SourceKey { len: metadata.len(), mtime_nanos: mtime_nanos(&metadata), hash })
+    }
+}
+
+fn mtime_nanos(metadata: &Metadata) -> u128 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_nanos())
+}
+
+/// Every data line of a log, parsed once, so queries can skip reparsing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Index {
+    source: SourceKey,
+    total_lines: usize,
+    entries: Vec<Entry>,
+}
+
+impl Index {
+    /// Parses `file_path` into an index.
+    pub fn build(file_path: &Path) -> Result<Index, String> {
+        let source = SourceKey::of(file_path)?;
+        let file = File::open(file_path)
+            .map_err(|e| format!("Failed to open file: {}", e))?;
+
+        let mut total_lines = 0;
+        let mut entries = Vec::new();
+        for (idx, line_result) in BufReader::new(file).lines().enumerate() {
+            let line_number = idx + 1;
+            total_lines += 1;
+            match line_result {
+                Err(e) => entries.push(Entry::Unreadable(line_number, e.to_string())),
+                Ok(line) if is_comment_or_metadata(&line) => {}
+                Ok(line) => entries.push(match Mission::from_line(&line, line_number) {
+                    Some(m) => Entry::Parsed(m),
+                    None => Entry::Invalid(line_number),
+                }),
+            }
+        }
+
+        if SourceKey::of(file_path)? != source {
+            return Err(format!("{} changed while it was being indexed; try again", file_path.display()));
+        }
+        Ok(Index { source, total_lines, entries })
+    }
+
+    pub fn total_lines(&self) -> usize {
+        self.total_lines
+    }
+
+    pub fn missions(&self) -> usize {
+        self.entries.iter().filter(|e| matches!(e, Entry::Parsed(_))).count()
+    }
+
+    /// Runs the checks on every indexed line, exactly as scanning the log
+    /// itself would.
+    pub fn replay<F>(self, config: &Config, verbose: bool, visit: &mut F) -> Statistics
+    where
+        F: FnMut(Mission, Option<Rejection>),
+    {
+        let mut stats = Statistics { total_lines: self.total_lines, ..Default::default() };
+        for entry in self.entries {
+            match entry {
+                Entry::Parsed(m) => {
+                    let line_number = m.line_number;
+                    check_parsed(Some(m), line_number, config, verbose, &mut stats, visit);
+                }
+                Entry::Invalid(line_number) => check_parsed(None, line_number, config, verbose, &mut stats, visit),
+                Entry::Unreadable(line_number, error) => count_read_error(line_number, &error, verbose, &mut stats),
+            }
+        }
+        stats
+    }
+
+    fn record_batch(&self) -> Result<RecordBatch, String> {
+        let missions = || {
+            self.entries.iter().map(|e| match e {
+                Entry::Parsed(m) => Some(m),
+                _ => None,
+            })
+        };
+        let text = |get: fn(&Mission) -> &str| -> ArrayRef {
+            Arc::new(StringArray::from_iter(missions().map(|m| m.map(get))))
+        };
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(UInt64Array::from_iter_values(self.entries.iter().map(|e| match e {
+                Entry::Parsed(m) => m.line_number as u64,
+                Entry::Invalid(n) | Entry::Unreadable(n, _) => *n as u64,
+            }))),
+            Arc::new(UInt8Array::from_iter_values(self.entries.iter().map(|e| match e {
+                Entry::Parsed(_) => PARSED,
+                Entry::Invalid(_) => INVALID,
+                Entry::Unreadable(..) => UNREADABLE,
+            }))),
+            text(|m| &m.date),
+            text(|m| &m.mission_id),
+            text(|m| &m.destination),
+            text(|m| &m.status),
+            Arc::new(UInt32Array::from_iter(missions().map(|m| m.map(|m| m.crew_size)))),
+            Arc::new(UInt32Array::from_iter(missions().map(|m| m.map(|m| m.duration)))),
+            Arc::new(Float64Array::from_iter(missions().map(|m| m.map(|m| m.success_rate)))),
+            text(|m| &m.security_code),
+            Arc::new(StringArray::from_iter(self.entries.iter().map(|e| match e {
+                Entry::Unreadable(_, error) => Some(error.as_str()),
+                _ => None,
+            }))),
+        ];
+
+        let metadata = HashMap::from([
+            ("version".to_string(), VERSION.to_string()),
+            ("source_len".to_string(), self.source.len.to_string()),
+            ("source_mtime_nanos".to_string(), self.source.mtime_nanos.to_string()),
+            ("source_hash".to_string(), self.source.hash.to_string()),
+            ("total_lines".to_string(), self.total_lines.to_string()),
+        ]);
+        let schema = Schema::new(schema_fields()).with_metadata(metadata);
+
+        RecordBatch::try_new(Arc::new(schema), columns)
+            .map_err(|e| format!("Failed to build record batch: {}", e))
+    }
+
+    /// Writes the index as an Arrow IPC file, via a temporary file so a
+    /// reader never sees a half-written index.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let batch = self.record_batch()?;
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+
+        let file = File::create(&tmp)
+            .map_err(|e| format!("Failed to create {}: {}", tmp.display(), e))?;
+        let mut writer = arrow_ipc::writer::FileWriter::try_new(file, &batch.schema())
+            .map_err(|e| format!("Failed to create Arrow writer: {}", e))?;
+        writer
+            .write(&batch)
+            .map_err(|e| format!("Failed to write Arrow data: {}", e))?;
+        writer
+            .finish()
+            .map_err(|e| format!("Failed to finish Arrow file: {}", e))?;
+
+        fs::rename(&tmp, path)
+            .map_err(|e| format!("Failed to write index {}: {}", path.display(), e))
+    }
+
+    pub fn load(path: &Path) -> Result<Index, String> {
+        let file = File::open(path)
+            .map_err(|e| format!("Failed to open index: {}", e))?;
+        let reader = arrow_ipc::reader::FileReader::try_new(file, None)
+            .map_err(|e| format!("Invalid index {}: {}", path.display(), e))?;
+
+        let schema = reader.schema();
+        let meta = |key: &str| -> Result<&str, String> {
+            schema
+                .metadata()
+                .get(key)
+                .map(String::as_str)
+                .ok_or_else(|| format!("Invalid index {}: missing {}", path.display(), key))
+        };
+        if meta("version")? != VERSION {
+            return Err(format!("Index {} was written by another version", path.display()));
+        }
+        let number = |key: &str| -> Result<u128, String> {
+            meta(key)?
+                .parse()
+                .map_err(|_| format!("Invalid index {}: bad {}", path.display(), key))
+        };
+        let source = SourceKey {
+            len: number("source_len")? as u64,
+            mtime_nanos: number("source_mtime_nanos")?,
+            hash: number("source_hash")? as u64,
+        };
+        let total_lines = number("total_lines")? as usize;
+
+        let mut entries = Vec::new();
+        for batch in reader {
+            let batch = batch.map_err(|e| format!("Invalid index {}: {}", path.display(), e))?;
+            entries.extend(entries_of(&batch).ok_or_else(|| format!("Invalid index {}: unexpected columns", path.display()))?);
+        }
+
+        Ok(Index { source, total_lines, entries })
+    }
+}
+
+fn schema_fields() -> Vec<Field> {
+    vec![
+        Field::new("line_number", DataType::UInt64, false),
+        Field::new("kind", DataType::UInt8, false),
+        Field::new("date", DataType::Utf8, true),
+        Field::new("mission_id", DataType::Utf8, true),
+        Field::new("destination", DataType::Utf8, true),
+        Field::new("status", DataType::Utf8, true),
+        Field::new("crew_size", DataType::UInt32, true),
+        Field::new("duration", DataType::UInt32, true),
+        Field::new("success_rate", DataType::Float64, true),
+        Field::new("security_code", DataType::Utf8, true),
+        Field::new("error", DataType::Utf8, true),
+    ]
+}
+
+fn entries_of(batch: &RecordBatch) -> Option<Vec<Entry>> {
+    let column = |idx: usize| batch.columns().get(idx);
+    let text = |idx: usize| column(idx)?.as_any().downcast_ref::<StringArray>();
+    let int = |idx: usize| column(idx)?.as_any().downcast_ref::<UInt32Array>();
+
+    let line_numbers = column(0)?.as_any().downcast_ref::<UInt64Array>()?;
+    let kinds = column(1)?.as_any().downcast_ref::<UInt8Array>()?;
+    let (date, mission_id, destination, status) = (text(2)?, text(3)?, text(4)?, text(5)?);
+    let (crew_size, duration) = (int(6)?, int(7)?);
+    let success_rate = column(8)?.as_any().downcast_ref::<Float64Array>()?;
+    let (security_code, error) = (text(9)?, text(10)?);
+
+    (0..batch.num_rows())
+        .map(|row| {
+            let line_number = line_numbers.value(row) as usize;
+            match kinds.value(row) {
+                PARSED => Some(Entry::Parsed(Mission {
+                    date: date.value(row).to_string(),
+                    mission_id: mission_id.value(row).to_string(),
+                    destination: destination.value(row).to_string(),
+                    status: status.value(row).to_string(),
+                    crew_size: crew_size.value(row),
+                    duration: duration.value(row),
+                    success_rate: success_rate.value(row),
+                    security_code: security_code.value(row).to_string(),
+                    line_number,
+                })),
+                INVALID => Some(Entry::Invalid(line_number)),
+                UNREADABLE => Some(Entry::Unreadable(line_number, error.value(row).to_string())),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// The index for `file_path`, if there is one and the log has not changed
+/// since it was built. Any problem with the index just means the log gets
+/// parsed as usual.
+pub fn load_current(file_path: &Path, verbose: bool) -> Option<Index> {
+    let path = index_path(file_path);
+    if !path.exists() {
+        return None;
+    }
+
+    let current = match SourceKey::of(file_path) {
+        Ok(key) => key,
+        Err(_) => return None,
+    };
+    match Index::load(&path) {
+        Ok(index) if index.source == current => Some(index),
+        Ok(_) => {
+            if verbose {
+                eprintln!("Warning: {} changed since {} was built; parsing it instead (run `index` to refresh)",
+                         file_path.display(), path.display());
+            }
+            None
+        }
+        Err(e) => {
+            if verbose {
+                eprintln!("Warning: Ignoring index: {}", e);
+            }
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const LOG: &str = "# header\n\
+        2045-01-01 | AAA-0001 | Mars | Completed | 3 | 300 | 90.5 | ABC-123-XYZ\n\
+        garbage\n\
+        2045-01-02 | BBB-0002 | Venus | Completed | 3 | 100 | 80.0 | ABC-123-XYZ\n";
+
+    fn write_log(dir: &tempfile::TempDir) -> PathBuf {
+        let path = dir.path().join("missions.log");
+        fs::write(&path, LOG).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = write_log(&dir);
+
+        let index = Index::build(&log).unwrap();
+        assert_eq!(index.total_lines(), 4);
+        assert_eq!(index.missions(), 2);
+        assert_eq!(index.entries[1], Entry::Invalid(3));
+
+        index.save(&index_path(&log)).unwrap();
+        assert_eq!(Index::load(&index_path(&log)).unwrap(), index);
+    }
+
+    #[test]
+    fn test_replay_matches_scan() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = write_log(&dir);
+        let config = Config::default();
+
+        let mut scanned = Vec::new();
+        let expected = crate::scan_file(&log, &config, false, |m, r| scanned.push((m.line_number, r))).unwrap();
+
+        let mut replayed = Vec::new();
+        let stats = Index::build(&log).unwrap().replay(&config, false, &mut |m, r| replayed.push((m.line_number, r)));
+
+        assert_eq!(replayed, scanned);
+        assert_eq!(serde_json::to_value(&stats).unwrap(), serde_json::to_value(&expected).unwrap());
+    }
+
+    #[test]
+    fn test_stale_index_is_ignored() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = write_log(&dir);
+        Index::build(&log).unwrap().save(&index_path(&log)).unwrap();
+        assert!(load_current(&log, false).is_some());
+
+        let mut file = fs::OpenOptions::new().append(true).open(&log).unwrap();
+        writeln!(file, "2045-01-03 | CCC-0003 | Mars | Completed | 3 | 900 | 90.0 | ABC-123-XYZ").unwrap();
+        assert!(load_current(&log, false).is_none());
+    }
+
+    #[test]
+    fn test_same_size_edit_with_old_mtime_is_stale() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = dir.path().join("missions.log");
+        let line = "2045-01-01 | AAA-0001 | Mars | Completed | 3 | 300 | 90.5 | ABC-123-XYZ\n";
+        let text = line.repeat(5000);
+        fs::write(&log, &text).unwrap();
+        Index::build(&log).unwrap().save(&index_path(&log)).unwrap();
+        let mtime = fs::metadata(&log).unwrap().modified().unwrap();
+
+        // Rewrite one status far from either end, keeping the size and mtime
+        let middle = text.len() / 2 / line.len() * line.len();
+        let edited = format!("{}{}{}", &text[..middle], line.replace("Completed", "Cancelled"), &text[middle + line.len()..]);
+        fs::write(&log, edited).unwrap();
+        File::options().write(true).open(&log).unwrap().set_modified(mtime).unwrap();
+
+        assert!(load_current(&log, false).is_none());
+    }
+}
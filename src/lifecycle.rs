@@ -0,0 +1,216 @@
+use crate::tabular::Cell;
+use crate::Mission;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Where a status sits in a mission's lifecycle. Statuses outside the known
+/// set are never judged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Stage {
+    Planned,
+    InProgress,
+    Finished,
+}
+
+fn stage_of(status: &str) -> Option<Stage> {
+    match status.to_ascii_lowercase().as_str() {
+        "planned" | "scheduled" => Some(Stage::Planned),
+        "in progress" | "in-progress" | "active" => Some(Stage::InProgress),
+        "completed" | "failed" | "aborted" | "cancelled" => Some(Stage::Finished),
+        _ => None,
+    }
+}
+
+/// A status change is legal when it moves forward through the lifecycle:
+/// Planned -> In Progress -> Completed/Failed/Aborted. Steps may be skipped,
+/// and repeating the same status is always fine.
+pub fn is_legal(from: &str, to: &str) -> bool {
+    if from.eq_ignore_ascii_case(to) {
+        return true;
+    }
+    match (stage_of(from), stage_of(to)) {
+        (Some(a), Some(b)) => a < b,
+        _ => true,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Transition {
+    pub from: String,
+    pub to: String,
+    pub from_line: usize,
+    pub to_line: usize,
+}
+
+/// Every record of one mission, in log order.
+#[derive(Debug, Clone)]
+pub struct Timeline<'a> {
+    pub mission_id: &'a str,
+    pub entries: Vec<&'a Mission>,
+}
+
+impl<'a> Timeline<'a> {
+    /// The latest record, which decides the mission's final state.
+    pub fn final_state(&self) -> &'a Mission {
+        self.entries[self.entries.len() - 1]
+    }
+
+    pub fn illegal_transitions(&self) -> Vec<Transition> {
+        self.entries
+            .windows(2)
+            .filter(|pair| !is_legal(&pair[0].status, &pair[1].status))
+            .map(|pair| Transition {
+                from: pair[0].status.clone(),
+                to: pair[1].status.clone(),
+                from_line: pair[0].line_number,
+                to_line: pair[1].line_number,
+            })
+            .collect()
+    }
+}
+
+/// Groups `missions` by mission ID, in order of first appearance.
+pub fn timelines(missions: &[Mission]) -> Vec<Timeline<'_>> {
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    let mut timelines: Vec<Timeline> = Vec::new();
+
+    for mission in missions {
+        let slot = *index.entry(mission.mission_id.as_str()).or_insert_with(|| {
+            timelines.push(Timeline { mission_id: &mission.mission_id, entries: Vec::new() });
+            timelines.len() - 1
+        });
+        timelines[slot].entries.push(mission);
+    }
+
+    timelines
+}
+
+/// One entry of a mission's history, with the raw log line it came from.
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryEntry {
+    pub line_number: usize,
+    pub date: String,
+    pub status: String,
+    /// Whether the move into this status was legal; always true for the first entry.
+    pub legal: bool,
+    pub source: String,
+}
+
+pub fn history(timeline: &Timeline, source_lines: &[String]) -> Vec<HistoryEntry> {
+    let mut previous: Option<&str> = None;
+    timeline
+        .entries
+        .iter()
+        .map(|m| {
+            let legal = previous.is_none_or(|p| is_legal(p, &m.status));
+            previous = Some(&m.status);
+            HistoryEntry {
+                line_number: m.line_number,
+                date: m.date.clone(),
+                status: m.status.clone(),
+                legal,
+                source: source_lines.get(m.line_number - 1).cloned().unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
+pub fn render_text(mission_id: &str, history: &[HistoryEntry]) -> String {
+    let mut out = format!(
+        "Timeline for {} ({} entr{})\n",
+        mission_id,
+        history.len(),
+        if history.len() == 1 { "y" } else { "ies" }
+    );
+    let last = history.len() - 1;
+    for (idx, entry) in history.iter().enumerate() {
+        let mut notes = Vec::new();
+        if !entry.legal {
+            notes.push("illegal transition");
+        }
+        if idx == last {
+            notes.push("final state");
+        }
+        let notes = if notes.is_empty() { String::new() } else { format!("  <- {}", notes.join(", ")) };
+        out.push_str(&format!("  line {}: {} {}{}\n", entry.line_number, entry.date, entry.status, notes));
+        out.push_str(&format!("    {}\n", entry.source));
+    }
+    out
+}
+
+pub const HEADERS: [&str; 5] = ["Line", "Date", "Status", "Legal", "Source"];
+
+pub fn rows(history: &[HistoryEntry]) -> Vec<Vec<Cell>> {
+    history
+        .iter()
+        .map(|e| {
+            vec![
+                Cell::Int(e.line_number as i64),
+                Cell::text(e.date.as_str()),
+                Cell::text(e.status.as_str()),
+                Cell::text(if e.legal { "yes" } else { "no" }),
+                Cell::text(e.source.as_str()),
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn missions() -> Vec<Mission> {
+        [
+            "2045-01-01 | A | Mars | Planned | 3 | 0 | 0.0 | ABC-123-XYZ",
+            "2045-01-01 | B | Mars | Completed | 3 | 100 | 90.0 | ABC-123-XYZ",
+            "2045-01-01 | A | Mars | In Progress | 3 | 10 | 50.0 | ABC-123-XYZ",
+            "2045-01-01 | B | Mars | In Progress | 3 | 100 | 90.0 | ABC-123-XYZ",
+            "2045-01-01 | A | Mars | Completed | 3 | 500 | 95.0 | ABC-123-XYZ",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(idx, l)| Mission::from_line(l, idx + 1).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn test_is_legal() {
+        assert!(is_legal("Planned", "In Progress"));
+        assert!(is_legal("Planned", "Completed"));
+        assert!(is_legal("completed", "Completed"));
+        assert!(is_legal("Mystery", "Completed"));
+        assert!(!is_legal("Completed", "In Progress"));
+        assert!(!is_legal("Failed", "Completed"));
+    }
+
+    #[test]
+    fn test_timelines() {
+        let missions = missions();
+        let timelines = timelines(&missions);
+
+        assert_eq!(timelines.len(), 2);
+        assert_eq!(timelines[0].mission_id, "A");
+        assert_eq!(timelines[0].final_state().line_number, 5);
+        assert!(timelines[0].illegal_transitions().is_empty());
+        assert_eq!(timelines[1].illegal_transitions(), vec![Transition {
+            from: "Completed".into(),
+            to: "In Progress".into(),
+            from_line: 2,
+            to_line: 4,
+        }]);
+    }
+
+    #[test]
+    fn test_history() {
+        let missions = missions();
+        let timelines = timelines(&missions);
+        let lines: Vec<String> = (1..=5).map(|n| format!("line {}", n)).collect();
+        let history = history(&timelines[1], &lines);
+
+        assert_eq!(history.len(), 2);
+        assert!(history[0].legal);
+        assert!(!history[1].legal);
+        assert_eq!(history[1].source, "line 4");
+        assert!(render_text("B", &history).contains("line 4: 2045-01-01 In Progress  <- illegal transition, final state"));
+    }
+}
@@ -0,0 +1,178 @@
+use crate::rules::Severity;
+use crate::tabular::Cell;
+use crate::validation::Config;
+use crate::{is_comment_or_metadata, Mission};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// One problem found on one line.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub line_number: usize,
+    pub severity: Severity,
+    pub rule: String,
+    pub message: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct Summary {
+    pub lines: usize,
+    pub records: usize,
+    pub invalid_records: usize,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+/// Explains why `line` could not be parsed into a `Mission`.
+pub fn format_problem(line: &str) -> String {
+    let parts: Vec<&str> = line.split('|').map(str::trim).collect();
+    if parts.len() < 8 {
+        return format!("expected 8 '|'-separated fields, found {}", parts.len());
+    }
+    if parts[4].parse::<u32>().is_err() {
+        return format!("crew_size '{}' is not a non-negative integer", parts[4]);
+    }
+    if parts[5].parse::<u32>().is_err() {
+        return format!("duration '{}' is not a non-negative integer", parts[5]);
+    }
+    format!("success_rate '{}' is not a number", parts[6])
+}
+
+/// Every rule and configured check `mission` fails, regardless of its
+/// destination or status.
+pub fn lint_mission(mission: &Mission, config: &Config) -> Vec<(Severity, String, String)> {
+    let mut problems: Vec<_> = config
+        .rules
+        .check(mission)
+        .into_iter()
+        .map(|v| (v.severity, v.rule, v.message))
+        .collect();
+
+    if config.mission_id.rejects(&mission.mission_id) {
+        problems.push((
+            Severity::Error,
+            "invalid_mission_id".to_string(),
+            format!("mission_id '{}' does not match {}", mission.mission_id, config.mission_id.pattern.as_str()),
+        ));
+    }
+
+    if let Some(v) = config.ranges.violation(mission) {
+        problems.push((
+            Severity::Error,
+            v.rejection.as_str().to_string(),
+            format!("{} {} is out of range ({})", v.field, v.value, v.range),
+        ));
+    }
+
+    problems
+}
+
+/// Checks every data line of `path`, ignoring the ranking filters.
+pub fn lint_file(path: &Path, config: &Config) -> Result<(Vec<Diagnostic>, Summary), String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut diagnostics = Vec::new();
+    let mut summary = Summary::default();
+
+    for (idx, line_result) in BufReader::new(file).lines().enumerate() {
+        let line_number = idx + 1;
+        summary.lines += 1;
+
+        let problems = match line_result {
+            Err(e) => vec![(Severity::Error, "read_error".to_string(), e.to_string())],
+            Ok(line) if is_comment_or_metadata(&line) => continue,
+            Ok(line) => {
+                summary.records += 1;
+                match Mission::from_line(&line, line_number) {
+                    Some(mission) => lint_mission(&mission, config),
+                    None => vec![(Severity::Error, "invalid_format".to_string(), format_problem(&line))],
+                }
+            }
+        };
+
+        if problems.iter().any(|(severity, _, _)| *severity == Severity::Error) {
+            summary.invalid_records += 1;
+        }
+        for (severity, rule, message) in problems {
+            match severity {
+                Severity::Error => summary.errors += 1,
+                Severity::Warn => summary.warnings += 1,
+            }
+            diagnostics.push(Diagnostic { line_number, severity, rule, message });
+        }
+    }
+
+    Ok((diagnostics, summary))
+}
+
+/// Compiler-style `file:line: severity: message [rule]` lines.
+pub fn render_text(source: &str, diagnostics: &[Diagnostic], summary: &Summary) -> String {
+    let mut out = String::new();
+    for d in diagnostics {
+        out.push_str(&format!(
+            "{}:{}: {}: {} [{}]\n",
+            source,
+            d.line_number,
+            d.severity.as_str(),
+            d.message,
+            d.rule
+        ));
+    }
+    out.push_str(&format!(
+        "Checked {} record{}: {} error{}, {} warning{} ({} invalid record{})\n",
+        summary.records,
+        if summary.records == 1 { "" } else { "s" },
+        summary.errors,
+        if summary.errors == 1 { "" } else { "s" },
+        summary.warnings,
+        if summary.warnings == 1 { "" } else { "s" },
+        summary.invalid_records,
+        if summary.invalid_records == 1 { "" } else { "s" },
+    ));
+    out
+}
+
+pub const HEADERS: [&str; 4] = ["Line", "Severity", "Rule", "Message"];
+
+pub fn rows(diagnostics: &[Diagnostic]) -> Vec<Vec<Cell>> {
+    diagnostics
+        .iter()
+        .map(|d| {
+            vec![
+                Cell::Int(d.line_number as i64),
+                Cell::text(d.severity.as_str()),
+                Cell::text(d.rule.as_str()),
+                Cell::text(d.message.as_str()),
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_problem() {
+        assert_eq!(format_problem("a | b | c"), "expected 8 '|'-separated fields, found 3");
+        assert_eq!(
+            format_problem("2045-01-01 | M | Mars | Completed | four | 10 | 9.0 | ABC-123-XYZ"),
+            "crew_size 'four' is not a non-negative integer"
+        );
+        assert_eq!(
+            format_problem("2045-01-01 | M | Mars | Completed | 4 | 10 | n/a | ABC-123-XYZ"),
+            "success_rate 'n/a' is not a number"
+        );
+    }
+
+    #[test]
+    fn test_lint_mission_ignores_destination_and_status() {
+        let config = Config::default();
+        let venus = Mission::from_line("2045-01-01 | V | Venus | Failed | 0 | 0 | 150.0 | bad", 1).unwrap();
+        let rules: Vec<String> = lint_mission(&venus, &config).into_iter().map(|p| p.1).collect();
+
+        // Zero duration is only a problem for completed missions
+        assert_eq!(rules, vec!["invalid_security_code", "crew_size_out_of_range"]);
+    }
+}
@@ -0,0 +1,126 @@
+use crate::tabular::Cell;
+use crate::{Mission, Rejection};
+use serde::Serialize;
+
+/// One mission found by `show`, with where it came from and whether it
+/// made the ranking.
+#[derive(Debug, Clone, Serialize)]
+pub struct Found {
+    pub file: String,
+    #[serde(flatten)]
+    pub mission: Mission,
+    pub rejection: Option<&'static str>,
+}
+
+/// Records whose mission ID equals `query`; failing that, records whose ID
+/// starts with it. Both comparisons ignore ASCII case.
+pub fn find<'a>(records: &'a [(Mission, Option<Rejection>)], query: &str) -> Vec<&'a (Mission, Option<Rejection>)> {
+    let exact: Vec<_> = records
+        .iter()
+        .filter(|(m, _)| m.mission_id.eq_ignore_ascii_case(query))
+        .collect();
+    if !exact.is_empty() {
+        return exact;
+    }
+
+    let prefix = query.to_ascii_lowercase();
+    records
+        .iter()
+        .filter(|(m, _)| m.mission_id.to_ascii_lowercase().starts_with(&prefix))
+        .collect()
+}
+
+pub fn render_text(found: &[Found]) -> String {
+    let blocks: Vec<String> = found
+        .iter()
+        .map(|f| {
+            let m = &f.mission;
+            let verdict = match f.rejection {
+                None => "eligible".to_string(),
+                Some(reason) => format!("excluded ({})", reason),
+            };
+            format!(
+                "{} ({}:{})\n  Date:          {}\n  Destination:   {}\n  Status:        {}\n  Crew Size:     {}\n  Duration:      {} days\n  Success Rate:  {}%\n  Security Code: {}\n  Ranking:       {}\n",
+                m.mission_id, f.file, m.line_number, m.date, m.destination, m.status,
+                m.crew_size, m.duration, m.success_rate, m.security_code, verdict
+            )
+        })
+        .collect();
+    blocks.join("\n")
+}
+
+pub const HEADERS: [&str; 11] = [
+    "File",
+    "Line",
+    "Date",
+    "Mission ID",
+    "Destination",
+    "Status",
+    "Crew Size",
+    "Duration (days)",
+    "Success Rate",
+    "Security Code",
+    "Rejection",
+];
+
+pub fn rows(found: &[Found]) -> Vec<Vec<Cell>> {
+    found
+        .iter()
+        .map(|f| {
+            let m = &f.mission;
+            vec![
+                Cell::text(f.file.as_str()),
+                Cell::Int(m.line_number as i64),
+                Cell::text(m.date.as_str()),
+                Cell::text(m.mission_id.as_str()),
+                Cell::text(m.destination.as_str()),
+                Cell::text(m.status.as_str()),
+                Cell::Int(m.crew_size as i64),
+                Cell::Int(m.duration as i64),
+                Cell::Float(m.success_rate),
+                Cell::text(m.security_code.as_str()),
+                Cell::text(f.rejection.unwrap_or("")),
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records() -> Vec<(Mission, Option<Rejection>)> {
+        [
+            ("2045-01-01 | KLM-1234 | Mars | Completed | 3 | 100 | 90.0 | ABC-123-XYZ", None),
+            ("2045-01-01 | KLM-1299 | Venus | Completed | 3 | 100 | 90.0 | ABC-123-XYZ", Some(Rejection::NotMars)),
+            ("2045-01-01 | XYZ-0001 | Mars | Failed | 3 | 100 | 90.0 | ABC-123-XYZ", Some(Rejection::NotCompleted)),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (line, r))| (Mission::from_line(line, idx + 1).unwrap(), r))
+        .collect()
+    }
+
+    #[test]
+    fn test_find_exact_before_prefix() {
+        let records = records();
+
+        assert_eq!(find(&records, "klm-1234").len(), 1);
+        assert_eq!(find(&records, "KLM-12").len(), 2);
+        assert!(find(&records, "NOPE").is_empty());
+    }
+
+    #[test]
+    fn test_render_text() {
+        let records = records();
+        let found = vec![Found {
+            file: "a.log".to_string(),
+            mission: records[1].0.clone(),
+            rejection: records[1].1.map(Rejection::as_str),
+        }];
+        let out = render_text(&found);
+
+        assert!(out.starts_with("KLM-1299 (a.log:2)\n"));
+        assert!(out.contains("  Ranking:       excluded (not_mars)\n"));
+    }
+}
@@ -1,39 +1,321 @@
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
+use std::ops::AddAssign;
+use std::path::{Path, PathBuf};
 use std::process;
-
-#[derive(Debug, Clone, Copy, ValueEnum)]
+use std::thread;
+
+mod aggregate;
+mod anomalies;
+mod checkpoint;
+mod columnar;
+mod diff;
+mod explain;
+mod follow;
+mod hash;
+mod histogram;
+mod index;
+mod lifecycle;
+mod lint;
+mod lookup;
+mod merge;
+mod normalize;
+mod repl;
+mod report;
+mod rules;
+mod serve;
+mod sqlite;
+mod table;
+mod tabular;
+mod trends;
+mod tui;
+mod validation;
+
+use aggregate::GroupKey;
+use table::ColorChoice;
+use tabular::{Cell, Tabular};
+use trends::Period;
+use rules::{RuleSet, Severity};
+use validation::Config;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
 enum OutputFormat {
     Default,
     Json,
+    Ndjson,
     Csv,
+    Table,
+    Markdown,
+    Html,
+    Parquet,
+    Arrow,
 }
 
 #[derive(Parser, Debug)]
 #[command(name = "mars-mission-analyzer")]
 #[command(about = "Find the longest successful Mars missions", long_about = None)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Input log file to analyze
     input_file: Option<PathBuf>,
 
     /// Show detailed processing statistics and warnings
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     verbose: bool,
 
-    /// Output format: default, json, or csv
-    #[arg(short, long, value_enum, default_value = "default")]
+    /// Output format: default, json, ndjson, csv, table, markdown, html, parquet, or arrow
+    #[arg(short, long, value_enum, default_value = "default", global = true)]
     format: OutputFormat,
 
+    /// Colorize table output: auto, always, or never
+    #[arg(long, value_enum, default_value = "auto", global = true)]
+    color: ColorChoice,
+
+    /// TOML config file with plausibility ranges and the mission ID pattern
+    #[arg(long, value_name = "FILE", global = true)]
+    config: Option<PathBuf>,
+
+    /// TOML file of declarative validation rules, applied to every record; a
+    /// rule named zero_duration or invalid_security_code replaces that built-in check
+    #[arg(long, value_name = "FILE", global = true)]
+    rules: Option<PathBuf>,
+
+    /// Append a final statistics record to ndjson output (requires --format ndjson)
+    #[arg(long, conflicts_with_all = ["group_by", "explain", "follow"])]
+    ndjson_stats: bool,
+
     /// Show top N longest missions (default: 1)
     #[arg(short, long, default_value = "1")]
     top: usize,
+
+    /// Rank the top N separately for each group; grouping by destination
+    /// considers every destination, not just Mars
+    #[arg(long, value_enum, value_name = "KEY")]
+    group_by: Option<GroupKey>,
+
+    /// Drop missions whose duration, crew size, or success rate is a robust
+    /// outlier for their destination before ranking
+    #[arg(long)]
+    exclude_outliers: bool,
+
+    /// Keep one record per mission ID, chosen by this policy, before ranking
+    #[arg(long, value_enum, value_name = "POLICY")]
+    dedupe: Option<merge::Policy>,
+
+    /// Rank each mission ID by its last record only, warning (with --verbose)
+    /// about illegal status transitions
+    #[arg(long, conflicts_with = "dedupe")]
+    final_state: bool,
+
+    /// Trace a line number or mission ID through every check and print the
+    /// verdict at each stage instead of the ranking
+    #[arg(long, value_name = "LINE|MISSION_ID")]
+    explain: Option<String>,
+
+    /// Keep reading the input as it grows, like `tail -F`, and print the
+    /// top missions again (or NDJSON deltas) whenever they change
+    #[arg(
+        long,
+        conflicts_with_all = ["group_by", "dedupe", "final_state", "exclude_outliers", "explain", "export_sqlite"]
+    )]
+    follow: bool,
+
+    /// Keep the scan position, statistics, and top missions in FILE so the
+    /// next run only reads lines appended since; a rewritten log is rescanned
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["group_by", "dedupe", "final_state", "exclude_outliers", "explain", "export_sqlite", "follow"]
+    )]
+    state: Option<PathBuf>,
+
+    /// Write every parsed mission and the run statistics to a SQLite database
+    #[arg(long, value_name = "DB")]
+    export_sqlite: Option<PathBuf>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Aggregate duration and success rate over every parsed mission
+    Stats {
+        /// Input log file to analyze
+        input_file: PathBuf,
+
+        /// Field to group missions by
+        #[arg(short, long, value_enum, default_value = "destination")]
+        by: GroupKey,
+    },
+
+    /// Show the duration distribution of valid completed Mars missions
+    Histogram {
+        /// Input log file to analyze
+        input_file: PathBuf,
+
+        /// Number of equal-width buckets (default: 10, at most 10000)
+        #[arg(
+            short,
+            long,
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..=histogram::MAX_BUCKETS),
+            conflicts_with_all = ["bucket_width", "quantiles"]
+        )]
+        buckets: Option<usize>,
+
+        /// Fixed bucket width in days
+        #[arg(
+            short = 'w',
+            long,
+            value_name = "DAYS",
+            value_parser = clap::value_parser!(u32).range(1..),
+            conflicts_with = "quantiles"
+        )]
+        bucket_width: Option<u32>,
+
+        /// Number of quantile buckets holding roughly equal mission counts
+        #[arg(short, long, value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        quantiles: Option<usize>,
+    },
+
+    /// Show mission counts, completion rate, and averages over time
+    Trends {
+        /// Input log file to analyze
+        input_file: PathBuf,
+
+        /// Length of each period
+        #[arg(short, long, value_enum, default_value = "year")]
+        period: Period,
+
+        /// Only include missions to this destination (case-insensitive)
+        #[arg(short, long)]
+        destination: Option<String>,
+    },
+
+    /// Flag records with implausible values relative to their destination
+    Anomalies {
+        /// Input log file to analyze
+        input_file: PathBuf,
+
+        /// Robust statistic used to decide what counts as an outlier
+        #[arg(short, long, value_enum, default_value = "mad")]
+        method: anomalies::Method,
+
+        /// Robust z-score limit for mad (default: 3.5) or IQR multiplier for iqr (default: 1.5)
+        #[arg(short, long)]
+        threshold: Option<f64>,
+    },
+
+    /// Check every record of a log file and report each problem found
+    Validate {
+        /// Input log file to check
+        input_file: PathBuf,
+    },
+
+    /// Parse a log once into a columnar index (<input_file>.idx) that later
+    /// runs read instead of the log until the log changes
+    Index {
+        /// Input log file to index
+        input_file: PathBuf,
+    },
+
+    /// Rewrite a log in canonical form, keeping comments and metadata lines
+    Fmt {
+        /// Input log file to format
+        input_file: PathBuf,
+
+        /// Report lines that are not canonical and exit non-zero instead of printing
+        #[arg(long, conflicts_with = "in_place")]
+        check: bool,
+
+        /// Overwrite the input file instead of printing to stdout
+        #[arg(short, long)]
+        in_place: bool,
+    },
+
+    /// Compare two logs by mission ID; like diff(1), exits 0 when they match,
+    /// 1 when they differ, and 2 on errors
+    Diff {
+        /// Earlier version of the log
+        old_file: PathBuf,
+
+        /// Later version of the log
+        new_file: PathBuf,
+
+        /// Size of the ranking to compare
+        #[arg(short, long, default_value = "1")]
+        top: usize,
+    },
+
+    /// Combine logs into one record per mission ID and print the merged log
+    Merge {
+        /// Input log files, oldest first
+        #[arg(required = true)]
+        input_files: Vec<PathBuf>,
+
+        /// How to pick a record when a mission ID appears more than once
+        #[arg(short, long, value_enum, default_value = "last-seen")]
+        policy: merge::Policy,
+    },
+
+    /// Print every field of the missions matching an ID or ID prefix
+    Show {
+        /// Mission ID, or a prefix of one (case-insensitive)
+        mission_id: String,
+
+        /// Input log file to search
+        input_file: PathBuf,
+    },
+
+    /// Print every record of one mission, in log order, with its source lines
+    Timeline {
+        /// Mission ID to look up (exact match)
+        mission_id: String,
+
+        /// Input log file to search
+        input_file: PathBuf,
+    },
+
+    /// Report mission IDs that appear more than once across logs
+    Dedupe {
+        /// Input log files, oldest first
+        #[arg(required = true)]
+        input_files: Vec<PathBuf>,
+
+        /// Policy used to show which record a merge would keep
+        #[arg(short, long, value_enum, default_value = "last-seen")]
+        policy: merge::Policy,
+    },
+
+    /// Load a log once and query it interactively (type `help` inside)
+    Repl {
+        /// Input log file to explore
+        input_file: PathBuf,
+    },
+
+    /// Browse missions in a full-screen terminal UI that follows the log as it grows
+    Tui {
+        /// Input log file to browse
+        input_file: PathBuf,
+    },
+
+    /// Serve results as JSON over HTTP on 127.0.0.1, reloading logs when they change
+    Serve {
+        /// Input log files to serve
+        #[arg(required = true)]
+        input_files: Vec<PathBuf>,
+
+        /// Port to listen on; 0 picks a free one
+        #[arg(short, long, default_value = "8080")]
+        port: u16,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 struct Mission {
     date: String,
     mission_id: String,
@@ -46,7 +328,7 @@ struct Mission {
     line_number: usize,
 }
 
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 struct Statistics {
     total_lines: usize,
     data_lines: usize,
@@ -54,6 +336,73 @@ struct Statistics {
     completed_mars_missions: usize,
     valid_missions: usize,
     errors: usize,
+    /// Violations of `warn` rules and of the default mission ID pattern;
+    /// these never reject a mission.
+    warnings: usize,
+    rejections: Rejections,
+}
+
+/// Breakdown of `Statistics::errors` by the check that rejected the line.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct Rejections {
+    read_errors: usize,
+    invalid_format: usize,
+    zero_duration: usize,
+    invalid_security_code: usize,
+    invalid_mission_id: usize,
+    crew_size_out_of_range: usize,
+    duration_out_of_range: usize,
+    success_rate_out_of_range: usize,
+    rule_violations: usize,
+}
+
+// Both sums destructure their argument, so a new counter cannot be missed.
+impl AddAssign<&Statistics> for Statistics {
+    fn add_assign(&mut self, other: &Statistics) {
+        let Statistics {
+            total_lines,
+            data_lines,
+            mars_missions,
+            completed_mars_missions,
+            valid_missions,
+            errors,
+            warnings,
+            rejections,
+        } = other;
+        self.total_lines += total_lines;
+        self.data_lines += data_lines;
+        self.mars_missions += mars_missions;
+        self.completed_mars_missions += completed_mars_missions;
+        self.valid_missions += valid_missions;
+        self.errors += errors;
+        self.warnings += warnings;
+        self.rejections += rejections;
+    }
+}
+
+impl AddAssign<&Rejections> for Rejections {
+    fn add_assign(&mut self, other: &Rejections) {
+        let Rejections {
+            read_errors,
+            invalid_format,
+            zero_duration,
+            invalid_security_code,
+            invalid_mission_id,
+            crew_size_out_of_range,
+            duration_out_of_range,
+            success_rate_out_of_range,
+            rule_violations,
+        } = other;
+        self.read_errors += read_errors;
+        self.invalid_format += invalid_format;
+        self.zero_duration += zero_duration;
+        self.invalid_security_code += invalid_security_code;
+        self.invalid_mission_id += invalid_mission_id;
+        self.crew_size_out_of_range += crew_size_out_of_range;
+        self.duration_out_of_range += duration_out_of_range;
+        self.success_rate_out_of_range += success_rate_out_of_range;
+        self.rule_violations += rule_violations;
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -62,8 +411,15 @@ struct JsonOutput {
     missions: Vec<MissionOutput>,
 }
 
+#[derive(Debug, Serialize)]
+struct NdjsonStatistics<'a> {
+    statistics: &'a Statistics,
+}
+
 #[derive(Debug, Serialize)]
 struct MissionOutput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group: Option<String>,
     rank: usize,
     date: String,
     mission_id: String,
@@ -76,6 +432,46 @@ struct MissionOutput {
     line_number: usize,
 }
 
+/// Why a parsed mission was left out of the ranking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Rejection {
+    NotMars,
+    NotCompleted,
+    ZeroDuration,
+    InvalidSecurityCode,
+    InvalidMissionId,
+    CrewSizeOutOfRange,
+    DurationOutOfRange,
+    SuccessRateOutOfRange,
+    RuleViolation,
+}
+
+impl Rejection {
+    fn as_str(self) -> &'static str {
+        match self {
+            Rejection::NotMars => "not_mars",
+            Rejection::NotCompleted => "not_completed",
+            Rejection::ZeroDuration => "zero_duration",
+            Rejection::InvalidSecurityCode => "invalid_security_code",
+            Rejection::InvalidMissionId => "invalid_mission_id",
+            Rejection::CrewSizeOutOfRange => "crew_size_out_of_range",
+            Rejection::DurationOutOfRange => "duration_out_of_range",
+            Rejection::SuccessRateOutOfRange => "success_rate_out_of_range",
+            Rejection::RuleViolation => "rule_violation",
+        }
+    }
+
+    /// The rejection for a failed error rule: the default rules keep their
+    /// own counters, everything else is a rule violation.
+    fn from_rule(name: &str) -> Self {
+        match name {
+            "zero_duration" => Rejection::ZeroDuration,
+            "invalid_security_code" => Rejection::InvalidSecurityCode,
+            _ => Rejection::RuleViolation,
+        }
+    }
+}
+
 impl Mission {
     fn from_line(line: &str, line_number: usize) -> Option<Self> {
         let parts: Vec<&str> = line.split('|').collect();
@@ -105,207 +501,1462 @@ impl Mission {
             security_code,
             line_number,
         })
-    }
+    }
+
+    fn is_valid_security_code(&self) -> bool {
+        let re = Regex::new(r"^[A-Z]{3}-[0-9]{3}-[A-Z]{3}$").unwrap();
+        re.is_match(&self.security_code)
+    }
+
+    /// Destination and status are title-cased, the way `fmt` writes them.
+    fn to_output(&self, rank: usize) -> MissionOutput {
+        MissionOutput {
+            group: None,
+            rank,
+            date: self.date.clone(),
+            mission_id: self.mission_id.clone(),
+            destination: normalize::title_case(&self.destination),
+            status: normalize::title_case(&self.status),
+            crew_size: self.crew_size,
+            duration_days: self.duration,
+            success_rate: self.success_rate,
+            security_code: self.security_code.clone(),
+            line_number: self.line_number,
+        }
+    }
+
+    /// Like `to_output`, but tags the record with its leaderboard group.
+    fn to_group_output(&self, rank: usize, group: &str) -> MissionOutput {
+        MissionOutput {
+            group: Some(group.to_string()),
+            ..self.to_output(rank)
+        }
+    }
+
+    /// Every ranking check except the destination filter.
+    fn is_successful(&self, config: &Config) -> bool {
+        !config.rules.has_errors(self)
+            && self.status.eq_ignore_ascii_case("completed")
+            && !config.mission_id.rejects(&self.mission_id)
+            && config.ranges.violation(self).is_none()
+    }
+
+    #[allow(dead_code)]
+    fn is_completed_mars_mission(&self) -> bool {
+        self.destination.eq_ignore_ascii_case("mars")
+            && self.status.eq_ignore_ascii_case("completed")
+            && self.duration > 0
+            && self.is_valid_security_code()
+    }
+}
+
+fn is_comment_or_metadata(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty()
+        || trimmed.starts_with('#')
+        || trimmed.starts_with("SYSTEM:")
+        || trimmed.starts_with("CONFIG:")
+        || trimmed.starts_with("CHECKSUM:")
+}
+
+fn process_file(
+    file_path: &PathBuf,
+    config: &Config,
+    verbose: bool,
+) -> Result<(Vec<Mission>, Statistics), String> {
+    let mut missions = Vec::new();
+
+    let stats = scan_file(file_path, config, verbose, |mission, rejection| {
+        if rejection.is_none() {
+            missions.push(mission);
+        }
+    })?;
+
+    Ok((missions, stats))
+}
+
+/// Parses every data line of `file_path`, updating statistics and handing
+/// each successfully parsed mission to `visit` along with the reason it was
+/// rejected from the ranking, if any.
+fn scan_file<F>(
+    file_path: &PathBuf,
+    config: &Config,
+    verbose: bool,
+    mut visit: F,
+) -> Result<Statistics, String>
+where
+    F: FnMut(Mission, Option<Rejection>),
+{
+    if let Some(index) = index::load_current(file_path, verbose) {
+        return Ok(index.replay(config, verbose, &mut visit));
+    }
+
+    let file = File::open(file_path)
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+
+    let reader = BufReader::new(file);
+    let mut stats = Statistics::default();
+
+    for (idx, line_result) in reader.lines().enumerate() {
+        let line_number = idx + 1;
+        stats.total_lines += 1;
+
+        let line = match line_result {
+            Ok(l) => l,
+            Err(e) => {
+                count_read_error(line_number, &e, verbose, &mut stats);
+                continue;
+            }
+        };
+
+        scan_line(&line, line_number, config, verbose, &mut stats, &mut visit);
+    }
+
+    Ok(stats)
+}
+
+fn count_read_error(line_number: usize, error: &dyn std::fmt::Display, verbose: bool, stats: &mut Statistics) {
+    if verbose {
+        eprintln!("Warning: Failed to read line {}: {}", line_number, error);
+    }
+    stats.errors += 1;
+    stats.rejections.read_errors += 1;
+}
+
+/// Runs every check on one line that was read successfully. `total_lines`
+/// is left to the caller, which also counts lines that failed to read.
+fn scan_line<F>(
+    line: &str,
+    line_number: usize,
+    config: &Config,
+    verbose: bool,
+    stats: &mut Statistics,
+    visit: &mut F,
+) where
+    F: FnMut(Mission, Option<Rejection>),
+{
+    // Skip comments and metadata
+    if is_comment_or_metadata(line) {
+        return;
+    }
+
+    check_parsed(Mission::from_line(line, line_number), line_number, config, verbose, stats, visit);
+}
+
+/// Runs the checks that follow parsing on a data line; `mission` is `None`
+/// when the line did not parse. Shared by `scan_line` and index replay.
+fn check_parsed<F>(
+    mission: Option<Mission>,
+    line_number: usize,
+    config: &Config,
+    verbose: bool,
+    stats: &mut Statistics,
+    visit: &mut F,
+) where
+    F: FnMut(Mission, Option<Rejection>),
+{
+    stats.data_lines += 1;
+
+    let mission = match mission {
+        Some(m) => m,
+        None => {
+            if verbose {
+                eprintln!("Warning: Line {} has invalid format or missing fields", line_number);
+            }
+            stats.errors += 1;
+            stats.rejections.invalid_format += 1;
+            return;
+        }
+    };
+
+    let is_mars = mission.destination.eq_ignore_ascii_case("mars");
+    let is_completed = mission.status.eq_ignore_ascii_case("completed");
+    if is_mars {
+        stats.mars_missions += 1;
+        if is_completed {
+            stats.completed_mars_missions += 1;
+        }
+    }
+
+    // Apply the validation rules, built-in defaults first, to every record
+    let mut rejection = None;
+    for violation in config.rules.check(&mission) {
+        if verbose {
+            eprintln!("Warning: Line {} violates {} rule '{}': {}",
+                     line_number, violation.severity.as_str(), violation.rule, violation.message);
+        }
+        match violation.severity {
+            Severity::Error => {
+                rejection.get_or_insert(Rejection::from_rule(&violation.rule));
+            }
+            Severity::Warn => stats.warnings += 1,
+        }
+    }
+    if let Some(rejection) = rejection {
+        stats.errors += 1;
+        match rejection {
+            Rejection::ZeroDuration => stats.rejections.zero_duration += 1,
+            Rejection::InvalidSecurityCode => stats.rejections.invalid_security_code += 1,
+            _ => stats.rejections.rule_violations += 1,
+        }
+        visit(mission, Some(rejection));
+        return;
+    }
+
+    // Check if it's a Mars mission
+    if !is_mars {
+        visit(mission, Some(Rejection::NotMars));
+        return;
+    }
+
+    // Check if it's completed
+    if !is_completed {
+        visit(mission, Some(Rejection::NotCompleted));
+        return;
+    }
+
+    // Validate mission ID; only a configured pattern rejects
+    if !config.mission_id.is_valid(&mission.mission_id) {
+        if verbose {
+            eprintln!("Warning: Line {} has invalid mission ID format: {}",
+                     line_number, mission.mission_id);
+        }
+        if config.mission_id.enforced {
+            stats.errors += 1;
+            stats.rejections.invalid_mission_id += 1;
+            visit(mission, Some(Rejection::InvalidMissionId));
+            return;
+        }
+        stats.warnings += 1;
+    }
+
+    // Validate plausibility ranges
+    if let Some(violation) = config.ranges.violation(&mission) {
+        if verbose {
+            eprintln!("Warning: Line {} has {} out of range ({}): {}",
+                     line_number, violation.field, violation.range, violation.value);
+        }
+        stats.errors += 1;
+        match violation.rejection {
+            Rejection::CrewSizeOutOfRange => stats.rejections.crew_size_out_of_range += 1,
+            Rejection::DurationOutOfRange => stats.rejections.duration_out_of_range += 1,
+            _ => stats.rejections.success_rate_out_of_range += 1,
+        }
+        visit(mission, Some(violation.rejection));
+        return;
+    }
+
+    stats.valid_missions += 1;
+    visit(mission, None);
+}
+
+fn print_statistics(stats: &Statistics) {
+    eprintln!("\n=== Processing Statistics ===");
+    for line in statistics_lines(stats) {
+        eprintln!("{}", line);
+    }
+    eprintln!("============================\n");
+}
+
+/// The body of the `--verbose` statistics summary, one entry per line.
+fn statistics_lines(stats: &Statistics) -> Vec<String> {
+    let mut lines = vec![
+        format!("Total lines processed: {}", stats.total_lines),
+        format!("Data lines: {}", stats.data_lines),
+        format!("Total Mars missions: {}", stats.mars_missions),
+        format!("Completed Mars missions: {}", stats.completed_mars_missions),
+        format!("Valid missions stored: {}", stats.valid_missions),
+        format!("Errors/warnings: {}", stats.errors),
+    ];
+    if stats.warnings > 0 {
+        lines.push(format!("Warnings: {}", stats.warnings));
+    }
+    lines
+}
+
+fn print_default_output(missions: &[Mission], verbose: bool, stats: &Statistics) {
+    if verbose {
+        print_statistics(stats);
+    }
+
+    let num_to_show = missions.len();
+
+    if verbose {
+        eprintln!("=== Results (Top {} Mission{}) ===",
+                 num_to_show, if num_to_show > 1 { "s" } else { "" });
+    }
+
+    print_mission_blocks(missions, verbose);
+}
+
+fn print_mission_blocks(missions: &[Mission], verbose: bool) {
+    let num_to_show = missions.len();
+
+    for (idx, mission) in missions.iter().enumerate() {
+        if num_to_show > 1 {
+            println!("\n--- Rank #{} ---", idx + 1);
+        }
+
+        if verbose {
+            for line in mission_details(mission) {
+                println!("{}", line);
+            }
+        } else {
+            println!("Security Code: {}", mission.security_code);
+            println!("Mission Length: {} days", mission.duration);
+        }
+    }
+}
+
+/// The fields `--verbose` prints for one mission, one entry per line.
+fn mission_details(mission: &Mission) -> Vec<String> {
+    vec![
+        format!("Date: {}", mission.date),
+        format!("Mission ID: {}", mission.mission_id),
+        format!("Crew Size: {}", mission.crew_size),
+        format!("Success Rate: {}%", mission.success_rate),
+        format!("Duration: {} days", mission.duration),
+        format!("Security Code: {}", mission.security_code),
+        format!("Found at line: {}", mission.line_number),
+    ]
+}
+
+fn print_json_output(missions: &[Mission], stats: &Statistics) {
+    let mission_outputs: Vec<MissionOutput> = missions
+        .iter()
+        .enumerate()
+        .map(|(idx, m)| m.to_output(idx + 1))
+        .collect();
+
+    let output = JsonOutput {
+        statistics: stats.clone(),
+        missions: mission_outputs,
+    };
+
+    match serde_json::to_string_pretty(&output) {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("Error serializing to JSON: {}", e),
+    }
+}
+
+fn write_ndjson_output<W: Write>(
+    out: &mut W,
+    missions: &[Mission],
+    stats: &Statistics,
+    include_stats: bool,
+) -> io::Result<()> {
+    for (idx, mission) in missions.iter().enumerate() {
+        serde_json::to_writer(&mut *out, &mission.to_output(idx + 1))?;
+        writeln!(out)?;
+        out.flush()?;
+    }
+
+    if include_stats {
+        serde_json::to_writer(&mut *out, &NdjsonStatistics { statistics: stats })?;
+        writeln!(out)?;
+    }
+
+    out.flush()
+}
+
+fn print_ndjson_output(missions: &[Mission], stats: &Statistics, include_stats: bool) {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    if let Err(e) = write_ndjson_output(&mut out, missions, stats, include_stats) {
+        eprintln!("Error writing NDJSON output: {}", e);
+    }
+}
+
+fn print_csv_output(missions: &[Mission]) {
+    println!("Rank,Date,Mission ID,Destination,Status,Crew Size,Duration (days),Success Rate,Security Code,Line Number");
+
+    for (idx, mission) in missions.iter().enumerate() {
+        let out = mission.to_output(idx + 1);
+        println!("{},{},{},{},{},{},{},{},{},{}",
+                 out.rank,
+                 out.date,
+                 out.mission_id,
+                 out.destination,
+                 out.status,
+                 out.crew_size,
+                 out.duration_days,
+                 out.success_rate,
+                 out.security_code,
+                 out.line_number);
+    }
+}
+
+const MISSION_TABLE_HEADERS: [&str; 10] = [
+    "Rank",
+    "Date",
+    "Mission ID",
+    "Destination",
+    "Status",
+    "Crew Size",
+    "Duration (days)",
+    "Success Rate",
+    "Security Code",
+    "Line Number",
+];
+
+fn mission_table_rows(missions: &[Mission]) -> Vec<Vec<String>> {
+    missions
+        .iter()
+        .enumerate()
+        .map(|(idx, m)| {
+            let out = m.to_output(idx + 1);
+            vec![
+                out.rank.to_string(),
+                out.date,
+                out.mission_id,
+                out.destination,
+                out.status,
+                out.crew_size.to_string(),
+                out.duration_days.to_string(),
+                out.success_rate.to_string(),
+                out.security_code,
+                out.line_number.to_string(),
+            ]
+        })
+        .collect()
+}
+
+fn print_table_output(missions: &[Mission], color: ColorChoice) {
+    let rows = mission_table_rows(missions);
+    print!(
+        "{}",
+        table::render_table(&MISSION_TABLE_HEADERS, &rows, table::terminal_width(), color.enabled())
+    );
+}
+
+fn print_markdown_output(missions: &[Mission]) {
+    let rows = mission_table_rows(missions);
+    print!("{}", table::render_markdown(&MISSION_TABLE_HEADERS, &rows));
+}
+
+const GROUPED_TABLE_HEADERS: [&str; 11] = [
+    "Group",
+    "Rank",
+    "Date",
+    "Mission ID",
+    "Destination",
+    "Status",
+    "Crew Size",
+    "Duration (days)",
+    "Success Rate",
+    "Security Code",
+    "Line Number",
+];
+
+#[derive(Debug, Serialize)]
+struct GroupedJsonOutput<'a> {
+    statistics: &'a Statistics,
+    group_by: &'static str,
+    groups: &'a [LeaderboardOutput],
+}
+
+#[derive(Debug, Serialize)]
+struct LeaderboardOutput {
+    group: String,
+    missions: Vec<MissionOutput>,
+}
+
+/// Prints a separate top-N leaderboard for every group. JSON nests the
+/// missions under their group; every other format flattens them with a
+/// leading group column.
+fn print_grouped_output(missions: &[Mission], key: GroupKey, top: usize, stats: &Statistics, args: &Args) {
+    let leaderboards: Vec<(String, Vec<&Mission>)> = aggregate::partition(missions, key)
+        .into_iter()
+        .map(|(label, mut members)| {
+            members.sort_by_key(|m| Reverse(m.duration));
+            members.truncate(top);
+            (label, members)
+        })
+        .collect();
+
+    if args.format == OutputFormat::Default {
+        if args.verbose {
+            print_statistics(stats);
+        }
+        for (idx, (label, members)) in leaderboards.iter().enumerate() {
+            if idx > 0 {
+                println!();
+            }
+            println!("=== {}: {} ===", key.as_str(), label);
+            let members: Vec<Mission> = members.iter().map(|m| (*m).clone()).collect();
+            print_mission_blocks(&members, args.verbose);
+        }
+        return;
+    }
+
+    let groups: Vec<LeaderboardOutput> = leaderboards
+        .iter()
+        .map(|(label, members)| LeaderboardOutput {
+            group: label.clone(),
+            missions: members
+                .iter()
+                .enumerate()
+                .map(|(idx, m)| m.to_group_output(idx + 1, label))
+                .collect(),
+        })
+        .collect();
+
+    let flattened: Vec<&MissionOutput> = groups.iter().flat_map(|g| g.missions.iter()).collect();
+    let rows: Vec<Vec<Cell>> = flattened
+        .iter()
+        .map(|m| {
+            vec![
+                Cell::text(m.group.clone().unwrap_or_default()),
+                Cell::Int(m.rank as i64),
+                Cell::text(m.date.as_str()),
+                Cell::text(m.mission_id.as_str()),
+                Cell::text(m.destination.as_str()),
+                Cell::text(m.status.as_str()),
+                Cell::Int(m.crew_size as i64),
+                Cell::Int(m.duration_days as i64),
+                Cell::Float(m.success_rate),
+                Cell::text(m.security_code.as_str()),
+                Cell::Int(m.line_number as i64),
+            ]
+        })
+        .collect();
+
+    let title = format!("Top {} missions by {}", top, key.as_str());
+    let json = GroupedJsonOutput {
+        statistics: stats,
+        group_by: key.as_str(),
+        groups: &groups,
+    };
+
+    tabular::print_tabular(
+        &Tabular {
+            title: &title,
+            headers: &GROUPED_TABLE_HEADERS,
+            rows: &rows,
+            json: &json,
+            ndjson: &flattened,
+        },
+        args.format,
+        args.color,
+    );
+}
+
+/// Parses every record in `file_path`, exiting on I/O failure.
+fn read_all_missions(file_path: &PathBuf, config: &Config, verbose: bool) -> (Vec<Mission>, Statistics) {
+    let mut missions = Vec::new();
+    let stats = match scan_file(file_path, config, verbose, |m, _| missions.push(m)) {
+        Ok(stats) => stats,
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            process::exit(1);
+        }
+    };
+
+    if verbose {
+        print_statistics(&stats);
+    }
+
+    if missions.is_empty() {
+        eprintln!("ERROR: No parseable missions found in the log file.");
+        process::exit(1);
+    }
+
+    (missions, stats)
+}
+
+#[derive(Debug, Serialize)]
+struct StatsOutput<'a> {
+    group_by: &'static str,
+    statistics: &'a Statistics,
+    groups: &'a [aggregate::GroupStats],
+}
+
+fn run_stats(file_path: &PathBuf, by: GroupKey, config: &Config, args: &Args) {
+    let (missions, stats) = read_all_missions(file_path, config, args.verbose);
+    print_stats(&missions, by, &stats, args.format, args.color);
+}
+
+fn print_stats(missions: &[Mission], by: GroupKey, stats: &Statistics, format: OutputFormat, color: ColorChoice) {
+    let groups = aggregate::group_missions(missions, by);
+    let title = format!("Mission statistics by {}", by.as_str());
+
+    tabular::print_tabular(
+        &Tabular {
+            title: &title,
+            headers: &aggregate::HEADERS,
+            rows: &aggregate::rows(&groups),
+            json: &StatsOutput { group_by: by.as_str(), statistics: stats, groups: &groups },
+            ndjson: &groups,
+        },
+        format,
+        color,
+    );
+}
+
+#[derive(Debug, Serialize)]
+struct HistogramOutput<'a> {
+    statistics: &'a Statistics,
+    field: &'static str,
+    bucketing: &'static str,
+    total: usize,
+    buckets: &'a [histogram::Bucket],
+}
+
+fn run_histogram(
+    file_path: &PathBuf,
+    buckets: Option<usize>,
+    bucket_width: Option<u32>,
+    quantiles: Option<usize>,
+    config: &Config,
+    args: &Args,
+) {
+    let (missions, stats) = match process_file(file_path, config, args.verbose) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            process::exit(1);
+        }
+    };
+
+    if missions.is_empty() {
+        eprintln!("ERROR: No valid completed Mars missions found.");
+        process::exit(1);
+    }
+
+    let durations: Vec<u32> = missions.iter().map(|m| m.duration).collect();
+    let (bucketing, result) = match (bucket_width, quantiles) {
+        (Some(width), _) => match histogram::equal_width(&durations, width) {
+            Ok(result) => ("width", result),
+            Err(e) => {
+                eprintln!("ERROR: {}; choose a larger --bucket-width.", e);
+                process::exit(1);
+            }
+        },
+        (None, Some(n)) => ("quantile", histogram::quantiles(&durations, n)),
+        (None, None) => ("count", histogram::by_bucket_count(&durations, buckets.unwrap_or(10))),
+    };
+
+    if args.format == OutputFormat::Default {
+        if args.verbose {
+            print_statistics(&stats);
+        }
+        print!("{}", histogram::render_ascii(&result, table::terminal_width()));
+        return;
+    }
+
+    tabular::print_tabular(
+        &Tabular {
+            title: "Mission duration distribution",
+            headers: &histogram::HEADERS,
+            rows: &histogram::rows(&result),
+            json: &HistogramOutput {
+                statistics: &stats,
+                field: "duration",
+                bucketing,
+                total: durations.len(),
+                buckets: &result,
+            },
+            ndjson: &result,
+        },
+        args.format,
+        args.color,
+    );
+}
+
+#[derive(Debug, Serialize)]
+struct TrendsOutput<'a> {
+    statistics: &'a Statistics,
+    period: &'static str,
+    destination: Option<&'a str>,
+    series: &'a [trends::TrendPoint],
+}
+
+fn run_trends(
+    file_path: &PathBuf,
+    period: Period,
+    destination: Option<&str>,
+    config: &Config,
+    args: &Args,
+) {
+    let (mut missions, stats) = read_all_missions(file_path, config, args.verbose);
+
+    if let Some(destination) = destination {
+        missions.retain(|m| m.destination.eq_ignore_ascii_case(destination));
+        if missions.is_empty() {
+            eprintln!("ERROR: No missions found for destination '{}'.", destination);
+            process::exit(1);
+        }
+    }
+
+    let (series, undated) = trends::series(&missions, period);
+    if undated > 0 && args.verbose {
+        eprintln!("Warning: Skipped {} mission{} without a valid YYYY-MM-DD date",
+                 undated, if undated == 1 { "" } else { "s" });
+    }
+
+    let title = match destination {
+        Some(d) => format!("{} mission trends by {}", d, period.as_str()),
+        None => format!("Mission trends by {}", period.as_str()),
+    };
+
+    tabular::print_tabular(
+        &Tabular {
+            title: &title,
+            headers: &trends::HEADERS,
+            rows: &trends::rows(&series),
+            json: &TrendsOutput {
+                statistics: &stats,
+                period: period.as_str(),
+                destination,
+                series: &series,
+            },
+            ndjson: &series,
+        },
+        args.format,
+        args.color,
+    );
+}
+
+#[derive(Debug, Serialize)]
+struct AnomaliesOutput<'a> {
+    statistics: &'a Statistics,
+    method: &'static str,
+    threshold: f64,
+    anomalies: &'a [anomalies::Anomaly],
+}
+
+fn run_anomalies(
+    file_path: &PathBuf,
+    method: anomalies::Method,
+    threshold: Option<f64>,
+    config: &Config,
+    args: &Args,
+) {
+    let (missions, stats) = read_all_missions(file_path, config, args.verbose);
+    let threshold = threshold.unwrap_or(method.default_threshold());
+    let flagged = anomalies::detect(&missions, method, threshold);
+
+    if args.format == OutputFormat::Default && flagged.is_empty() {
+        println!("No anomalies found.");
+        return;
+    }
+
+    tabular::print_tabular(
+        &Tabular {
+            title: "Mission anomalies",
+            headers: &anomalies::HEADERS,
+            rows: &anomalies::rows(&flagged),
+            json: &AnomaliesOutput {
+                statistics: &stats,
+                method: method.as_str(),
+                threshold,
+                anomalies: &flagged,
+            },
+            ndjson: &flagged,
+        },
+        args.format,
+        args.color,
+    );
+}
+
+#[derive(Debug, Serialize)]
+struct ValidateOutput<'a> {
+    file: String,
+    summary: &'a lint::Summary,
+    diagnostics: &'a [lint::Diagnostic],
+}
+
+/// Prints every problem in `file_path` and exits non-zero if any is an error.
+fn run_validate(file_path: &Path, config: &Config, args: &Args) {
+    let (diagnostics, summary) = match lint::lint_file(file_path, config) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            process::exit(1);
+        }
+    };
+    let source = file_path.display().to_string();
+
+    if args.format == OutputFormat::Default {
+        print!("{}", lint::render_text(&source, &diagnostics, &summary));
+    } else {
+        tabular::print_tabular(
+            &Tabular {
+                title: "Validation diagnostics",
+                headers: &lint::HEADERS,
+                rows: &lint::rows(&diagnostics),
+                json: &ValidateOutput { file: source, summary: &summary, diagnostics: &diagnostics },
+                ndjson: &diagnostics,
+            },
+            args.format,
+            args.color,
+        );
+    }
+
+    if summary.errors > 0 {
+        process::exit(1);
+    }
+}
+
+/// Parses `file_path` once and saves the result next to it, where later
+/// scans of the same, unchanged log pick it up.
+fn run_index(file_path: &Path) {
+    let index = match index::Index::build(file_path) {
+        Ok(index) => index,
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            process::exit(1);
+        }
+    };
+    let path = index::index_path(file_path);
+    if let Err(e) = index.save(&path) {
+        eprintln!("ERROR: {}", e);
+        process::exit(1);
+    }
+    println!("Indexed {} lines ({} parsed missions) into {}", index.total_lines(), index.missions(), path.display());
+}
+
+/// Prints, rewrites, or checks the canonical form of `file_path`.
+fn run_fmt(file_path: &Path, check: bool, in_place: bool) {
+    let text = match fs::read_to_string(file_path) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("ERROR: Failed to read file: {}", e);
+            process::exit(1);
+        }
+    };
+    let (formatted, changed) = normalize::format_log(&text);
+
+    if check {
+        if formatted == text {
+            return;
+        }
+        for line_number in &changed {
+            println!("{}:{}: not in canonical form", file_path.display(), line_number);
+        }
+        if changed.is_empty() {
+            println!("{}: line endings differ from canonical form", file_path.display());
+        }
+        process::exit(1);
+    } else if in_place {
+        if formatted != text {
+            if let Err(e) = fs::write(file_path, formatted) {
+                eprintln!("ERROR: Failed to write file: {}", e);
+                process::exit(1);
+            }
+        }
+    } else {
+        print!("{}", formatted);
+    }
+}
+
+fn run_diff(old_file: &PathBuf, new_file: &PathBuf, top: usize, config: &Config, args: &Args) {
+    let load = |path: &PathBuf| match diff::Snapshot::load(path, config) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            eprintln!("ERROR: {}: {}", path.display(), e);
+            process::exit(2);
+        }
+    };
+    let changes = diff::compare(&load(old_file), &load(new_file), top);
+
+    if args.format == OutputFormat::Default {
+        print!("{}", diff::render_text(&changes));
+    } else {
+        let records = diff::records(&changes);
+        tabular::print_tabular(
+            &Tabular {
+                title: "Mission log diff",
+                headers: &diff::HEADERS,
+                rows: &diff::rows(&records),
+                json: &changes,
+                ndjson: &records,
+            },
+            args.format,
+            args.color,
+        );
+    }
+
+    if !changes.is_empty() || changes.ranking.changed {
+        process::exit(1);
+    }
+}
+
+/// Prints conflicts left unresolved by `--policy report` to stderr.
+fn print_conflicts(merged: &merge::Merged, sources: &[String]) {
+    for conflict in merged.conflicts() {
+        let seen: Vec<String> = conflict
+            .occurrences
+            .iter()
+            .map(|o| format!("{}:{}", sources[o.source], o.line_number))
+            .collect();
+        eprintln!("Conflict: {} differs in {} ({})",
+                 conflict.mission_id, conflict.conflicting_fields.join(", "), seen.join(", "));
+    }
+}
+
+fn merge_files(input_files: &[PathBuf], policy: merge::Policy, config: &Config, args: &Args) -> (merge::Merged, Vec<String>) {
+    // An empty shard contributes nothing but keeps its place, so record
+    // origins still index `input_files`
+    let parsed: Vec<Vec<Mission>> = input_files
+        .iter()
+        .map(|path| {
+            let mut missions = Vec::new();
+            match scan_file(path, config, args.verbose, |m, _| missions.push(m)) {
+                Ok(stats) if args.verbose => print_statistics(&stats),
+                Ok(_) => {}
+                Err(e) => {
+                    eprintln!("ERROR: {}", e);
+                    process::exit(1);
+                }
+            }
+            if missions.is_empty() {
+                eprintln!("Warning: {}: No parseable missions found; skipping it.", path.display());
+            }
+            missions
+        })
+        .collect();
+    if parsed.iter().all(Vec::is_empty) {
+        eprintln!("ERROR: No parseable missions found in the input files.");
+        process::exit(1);
+    }
+    let sources = input_files.iter().map(|p| p.display().to_string()).collect();
+    (merge::merge(&parsed, policy), sources)
+}
+
+/// Writes the merged log to stdout; refuses to when `--policy report`
+/// finds conflicting duplicates.
+fn run_merge(input_files: &[PathBuf], policy: merge::Policy, config: &Config, args: &Args) {
+    let (merged, sources) = merge_files(input_files, policy, config, args);
+
+    if merged.conflicts().next().is_some() {
+        print_conflicts(&merged, &sources);
+        eprintln!("ERROR: Conflicting duplicates found; choose a --policy other than report to resolve them.");
+        process::exit(1);
+    }
+
+    if args.verbose {
+        eprintln!("Merged {} file(s) into {} missions ({} duplicated IDs)",
+                 sources.len(), merged.missions.len(), merged.duplicates.len());
+    }
+
+    // Re-read the sources so each kept record is printed from its own text
+    // in `fmt`'s canonical form, numbers spelled as they were written
+    let texts: Vec<String> = input_files
+        .iter()
+        .map(|path| match fs::read(path) {
+            Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+            Err(e) => {
+                eprintln!("ERROR: {}: Failed to read file: {}", path.display(), e);
+                process::exit(1);
+            }
+        })
+        .collect();
+    let lines: Vec<Vec<&str>> = texts.iter().map(|text| text.lines().collect()).collect();
 
-    fn is_valid_security_code(&self) -> bool {
-        let re = Regex::new(r"^[A-Z]{3}-[0-9]{3}-[A-Z]{3}$").unwrap();
-        re.is_match(&self.security_code)
+    println!("# Merged from {} (policy: {})", sources.join(", "), policy.as_str());
+    for (origin, _) in &merged.missions {
+        println!("{}", normalize::canonical_line(lines[origin.source][origin.line_number - 1]));
     }
+}
 
-    #[allow(dead_code)]
-    fn is_completed_mars_mission(&self) -> bool {
-        self.destination.eq_ignore_ascii_case("mars")
-            && self.status.eq_ignore_ascii_case("completed")
-            && self.duration > 0
-            && self.is_valid_security_code()
+#[derive(Debug, Serialize)]
+struct DedupeOutput<'a> {
+    policy: &'static str,
+    sources: &'a [String],
+    duplicates: &'a [merge::Duplicate],
+}
+
+fn run_dedupe(input_files: &[PathBuf], policy: merge::Policy, config: &Config, args: &Args) {
+    let (merged, sources) = merge_files(input_files, policy, config, args);
+
+    if args.format == OutputFormat::Default && merged.duplicates.is_empty() {
+        println!("No duplicate mission IDs found.");
+        return;
     }
+
+    tabular::print_tabular(
+        &Tabular {
+            title: "Duplicate missions",
+            headers: &merge::HEADERS,
+            rows: &merge::rows(&merged.duplicates, &sources),
+            json: &DedupeOutput { policy: policy.as_str(), sources: &sources, duplicates: &merged.duplicates },
+            ndjson: &merged.duplicates,
+        },
+        args.format,
+        args.color,
+    );
 }
 
-fn is_comment_or_metadata(line: &str) -> bool {
-    let trimmed = line.trim();
-    trimmed.is_empty()
-        || trimmed.starts_with('#')
-        || trimmed.starts_with("SYSTEM:")
-        || trimmed.starts_with("CONFIG:")
-        || trimmed.starts_with("CHECKSUM:")
+fn run_show(query: &str, file_path: &PathBuf, config: &Config, args: &Args) {
+    let mut records = Vec::new();
+    if let Err(e) = scan_file(file_path, config, args.verbose, |m, r| records.push((m, r))) {
+        eprintln!("ERROR: {}", e);
+        process::exit(1);
+    }
+
+    let file = file_path.display().to_string();
+    let found: Vec<lookup::Found> = lookup::find(&records, query)
+        .into_iter()
+        .map(|(mission, rejection)| lookup::Found {
+            file: file.clone(),
+            mission: mission.clone(),
+            rejection: rejection.map(Rejection::as_str),
+        })
+        .collect();
+
+    if found.is_empty() {
+        eprintln!("ERROR: No mission ID matches {}", query);
+        process::exit(1);
+    }
+
+    print_found(&found, args.format, args.color);
 }
 
-fn process_file(file_path: &PathBuf, verbose: bool) -> Result<(Vec<Mission>, Statistics), String> {
-    let file = File::open(file_path)
-        .map_err(|e| format!("Failed to open file: {}", e))?;
+fn print_found(found: &[lookup::Found], format: OutputFormat, color: ColorChoice) {
+    if format == OutputFormat::Default {
+        print!("{}", lookup::render_text(found));
+        return;
+    }
 
-    let reader = BufReader::new(file);
-    let mut missions = Vec::new();
-    let mut stats = Statistics::default();
+    tabular::print_tabular(
+        &Tabular {
+            title: "Missions",
+            headers: &lookup::HEADERS,
+            rows: &lookup::rows(found),
+            json: &found,
+            ndjson: found,
+        },
+        format,
+        color,
+    );
+}
 
-    for (idx, line_result) in reader.lines().enumerate() {
-        let line_number = idx + 1;
-        stats.total_lines += 1;
+#[derive(Debug, Serialize)]
+struct TimelineOutput<'a> {
+    mission_id: &'a str,
+    final_status: &'a str,
+    illegal_transitions: &'a [lifecycle::Transition],
+    history: &'a [lifecycle::HistoryEntry],
+}
 
-        let line = match line_result {
-            Ok(l) => l,
-            Err(e) => {
-                if verbose {
-                    eprintln!("Warning: Failed to read line {}: {}", line_number, e);
-                }
-                stats.errors += 1;
-                continue;
-            }
-        };
+fn run_timeline(mission_id: &str, file_path: &PathBuf, config: &Config, args: &Args) {
+    let (missions, _) = read_all_missions(file_path, config, args.verbose);
+    let source_lines: Vec<String> = match fs::read_to_string(file_path) {
+        Ok(text) => text.lines().map(str::to_string).collect(),
+        Err(e) => {
+            eprintln!("ERROR: Failed to read file: {}", e);
+            process::exit(1);
+        }
+    };
 
-        // Skip comments and metadata
-        if is_comment_or_metadata(&line) {
-            continue;
+    let timelines = lifecycle::timelines(&missions);
+    let timeline = match timelines.iter().find(|t| t.mission_id == mission_id) {
+        Some(t) => t,
+        None => {
+            eprintln!("ERROR: No records found for mission ID {}", mission_id);
+            process::exit(1);
         }
+    };
+    let history = lifecycle::history(timeline, &source_lines);
 
-        stats.data_lines += 1;
+    if args.format == OutputFormat::Default {
+        print!("{}", lifecycle::render_text(mission_id, &history));
+        return;
+    }
 
-        // Parse the mission
-        let mission = match Mission::from_line(&line, line_number) {
-            Some(m) => m,
-            None => {
-                if verbose {
-                    eprintln!("Warning: Line {} has invalid format or missing fields", line_number);
-                }
-                stats.errors += 1;
-                continue;
-            }
-        };
+    tabular::print_tabular(
+        &Tabular {
+            title: "Mission timeline",
+            headers: &lifecycle::HEADERS,
+            rows: &lifecycle::rows(&history),
+            json: &TimelineOutput {
+                mission_id,
+                final_status: &timeline.final_state().status,
+                illegal_transitions: &timeline.illegal_transitions(),
+                history: &history,
+            },
+            ndjson: &history,
+        },
+        args.format,
+        args.color,
+    );
+}
 
-        // Check if it's a Mars mission
-        if !mission.destination.eq_ignore_ascii_case("mars") {
-            continue;
+/// Loads `file_path` once, then answers `repl` queries read from the
+/// terminal until `quit` or end of input.
+fn run_repl(file_path: &PathBuf, config: &Config, args: &Args) {
+    let mut records = Vec::new();
+    let stats = match scan_file(file_path, config, args.verbose, |m, r| records.push((m, r))) {
+        Ok(stats) => stats,
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            process::exit(1);
         }
-        stats.mars_missions += 1;
+    };
+
+    let mut format = args.format;
+    if matches!(format, OutputFormat::Html | OutputFormat::Parquet | OutputFormat::Arrow) {
+        eprintln!("ERROR: The REPL prints text only; use one of {}.", repl::text_formats().join(", "));
+        process::exit(1);
+    }
 
-        // Check if it's completed
-        if !mission.status.eq_ignore_ascii_case("completed") {
-            continue;
+    let mut editor = match rustyline::Editor::<repl::ReplHelper, rustyline::history::DefaultHistory>::new() {
+        Ok(editor) => editor,
+        Err(e) => {
+            eprintln!("ERROR: Failed to start the REPL: {}", e);
+            process::exit(1);
         }
-        stats.completed_mars_missions += 1;
+    };
+    editor.set_helper(Some(repl::ReplHelper));
+    let history = std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".mars_mission_analyzer_history"));
+    if let Some(path) = &history {
+        // A missing history file just means this is the first session
+        let _ = editor.load_history(path);
+    }
 
-        // Validate duration
-        if mission.duration == 0 {
-            if verbose {
-                eprintln!("Warning: Line {} has invalid duration: 0", line_number);
+    eprintln!("Loaded {} records from {}. Type 'help' for commands.", records.len(), file_path.display());
+
+    loop {
+        let line = match editor.readline("mars> ") {
+            Ok(line) => line,
+            Err(rustyline::error::ReadlineError::Interrupted) => continue,
+            Err(rustyline::error::ReadlineError::Eof) => break,
+            Err(e) => {
+                eprintln!("ERROR: {}", e);
+                break;
             }
-            stats.errors += 1;
-            continue;
+        };
+        let _ = editor.add_history_entry(line.as_str());
+
+        match repl::parse(&line) {
+            Ok(None) => {}
+            Ok(Some(repl::Query::Quit)) => break,
+            Ok(Some(repl::Query::Help)) => print!("{}", repl::HELP),
+            Ok(Some(repl::Query::Format(f))) => format = f,
+            Ok(Some(repl::Query::Top { count, by, conditions })) => {
+                // Like --group-by destination, a destination condition opens
+                // the ranking to successful missions anywhere
+                let any_destination = conditions.iter().any(|c| c.field == rules::Field::Destination);
+                let mut ranked: Vec<Mission> = records
+                    .iter()
+                    .filter(|(m, r)| match r {
+                        None => true,
+                        Some(Rejection::NotMars) => any_destination && m.is_successful(config),
+                        Some(_) => false,
+                    })
+                    .filter(|(m, _)| conditions.iter().all(|c| c.matches(m)))
+                    .map(|(m, _)| m.clone())
+                    .collect();
+                let value = |m: &Mission| by.number(m).unwrap_or(0.0);
+                ranked.sort_by(|a, b| value(b).total_cmp(&value(a)));
+                ranked.truncate(count.unwrap_or(args.top));
+
+                if ranked.is_empty() {
+                    eprintln!("No missions match.");
+                    continue;
+                }
+                match format {
+                    OutputFormat::Json => print_json_output(&ranked, &stats),
+                    OutputFormat::Ndjson => print_ndjson_output(&ranked, &stats, false),
+                    OutputFormat::Csv => print_csv_output(&ranked),
+                    OutputFormat::Table => print_table_output(&ranked, args.color),
+                    OutputFormat::Markdown => print_markdown_output(&ranked),
+                    // `format` only accepts text formats
+                    _ => print_mission_blocks(&ranked, args.verbose),
+                }
+            }
+            Ok(Some(repl::Query::Stats { by, conditions })) => {
+                let missions: Vec<Mission> = records
+                    .iter()
+                    .map(|(m, _)| m)
+                    .filter(|m| conditions.iter().all(|c| c.matches(m)))
+                    .cloned()
+                    .collect();
+                if missions.is_empty() {
+                    eprintln!("No missions match.");
+                    continue;
+                }
+                print_stats(&missions, by, &stats, format, args.color);
+            }
+            Ok(Some(repl::Query::Show(query))) => {
+                let file = file_path.display().to_string();
+                let found: Vec<lookup::Found> = lookup::find(&records, &query)
+                    .into_iter()
+                    .map(|(mission, rejection)| lookup::Found {
+                        file: file.clone(),
+                        mission: mission.clone(),
+                        rejection: rejection.map(Rejection::as_str),
+                    })
+                    .collect();
+                if found.is_empty() {
+                    eprintln!("No mission ID matches {}", query);
+                    continue;
+                }
+                print_found(&found, format, args.color);
+            }
+            Err(e) => eprintln!("ERROR: {}", e),
         }
+        let _ = io::stdout().flush();
+    }
 
-        // Validate security code
-        if !mission.is_valid_security_code() {
-            if verbose {
-                eprintln!("Warning: Line {} has invalid security code format: {}",
-                         line_number, mission.security_code);
+    if let Some(path) = &history {
+        if let Err(e) = editor.save_history(path) {
+            if args.verbose {
+                eprintln!("Warning: Failed to save history to {}: {}", path.display(), e);
             }
-            stats.errors += 1;
-            continue;
         }
-
-        stats.valid_missions += 1;
-        missions.push(mission);
     }
+}
 
-    Ok((missions, stats))
+fn run_serve(input_files: &[PathBuf], port: u16, config: &Config, args: &Args) {
+    let mut catalog = match serve::Catalog::load(input_files, config, args.verbose) {
+        Ok(catalog) => catalog,
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            process::exit(1);
+        }
+    };
+    let server = match serve::bind(port) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            process::exit(1);
+        }
+    };
+
+    eprintln!("Listening on http://{}", server.server_addr());
+    serve::run(&server, &mut catalog);
 }
 
-fn print_default_output(missions: &[Mission], verbose: bool, stats: &Statistics) {
-    if verbose {
-        eprintln!("\n=== Processing Statistics ===");
-        eprintln!("Total lines processed: {}", stats.total_lines);
-        eprintln!("Data lines: {}", stats.data_lines);
-        eprintln!("Total Mars missions: {}", stats.mars_missions);
-        eprintln!("Completed Mars missions: {}", stats.completed_mars_missions);
-        eprintln!("Valid missions stored: {}", stats.valid_missions);
-        eprintln!("Errors/warnings: {}", stats.errors);
-        eprintln!("============================\n");
+/// One NDJSON record per leaderboard change in `--follow` mode.
+#[derive(Debug, Serialize)]
+struct FollowDelta {
+    change: &'static str,
+    #[serde(flatten)]
+    mission: MissionOutput,
+}
+
+/// Entries entering, leaving, or moving within the leaderboard, comparing
+/// missions by line number and ID.
+fn leaderboard_deltas(before: &[Mission], after: &[Mission]) -> Vec<FollowDelta> {
+    let same = |a: &Mission, b: &Mission| a.line_number == b.line_number && a.mission_id == b.mission_id;
+    let mut deltas = Vec::new();
+
+    for (idx, old) in before.iter().enumerate() {
+        if !after.iter().any(|m| same(m, old)) {
+            deltas.push(FollowDelta { change: "removed", mission: old.to_output(idx + 1) });
+        }
     }
+    for (idx, new) in after.iter().enumerate() {
+        match before.iter().position(|m| same(m, new)) {
+            None => deltas.push(FollowDelta { change: "added", mission: new.to_output(idx + 1) }),
+            Some(old_idx) if old_idx != idx => {
+                deltas.push(FollowDelta { change: "moved", mission: new.to_output(idx + 1) })
+            }
+            Some(_) => {}
+        }
+    }
+    deltas
+}
 
-    let num_to_show = missions.len();
+/// Tails `file_path` forever, keeping running statistics and the top
+/// missions up to date and printing them whenever the leaderboard changes.
+fn run_follow(file_path: &Path, config: &Config, args: &Args) {
+    if !matches!(args.format, OutputFormat::Default | OutputFormat::Json | OutputFormat::Ndjson) {
+        eprintln!("ERROR: --follow supports only the default, json, and ndjson formats.");
+        process::exit(1);
+    }
 
-    if verbose {
-        eprintln!("=== Results (Top {} Mission{}) ===",
-                 num_to_show, if num_to_show > 1 { "s" } else { "" });
+    if args.verbose && !file_path.exists() {
+        eprintln!("Waiting for {} to appear", file_path.display());
     }
 
-    for (idx, mission) in missions.iter().enumerate() {
-        if num_to_show > 1 {
-            println!("\n--- Rank #{} ---", idx + 1);
+    let mut follower = follow::Follower::new(file_path);
+    let mut stats = Statistics::default();
+    let mut leaderboard: Vec<Mission> = Vec::new();
+
+    loop {
+        let batch = match follower.poll() {
+            Ok(batch) => batch,
+            Err(e) => {
+                eprintln!("ERROR: Failed to read {}: {}", file_path.display(), e);
+                process::exit(1);
+            }
+        };
+
+        if batch.truncated {
+            if args.verbose {
+                eprintln!("Warning: {} was truncated; starting over", file_path.display());
+            }
+            stats = Statistics::default();
+        }
+        if batch.rotated && args.verbose {
+            eprintln!("Warning: {} was rotated; following the new file", file_path.display());
         }
 
-        if verbose {
-            println!("Date: {}", mission.date);
-            println!("Mission ID: {}", mission.mission_id);
-            println!("Crew Size: {}", mission.crew_size);
-            println!("Success Rate: {}%", mission.success_rate);
-            println!("Duration: {} days", mission.duration);
-            println!("Security Code: {}", mission.security_code);
-            println!("Found at line: {}", mission.line_number);
-        } else {
-            println!("Security Code: {}", mission.security_code);
-            println!("Mission Length: {} days", mission.duration);
+        let before = if batch.truncated { Vec::new() } else { leaderboard.clone() };
+        if batch.truncated {
+            leaderboard.clear();
+        }
+
+        for (line_number, line) in &batch.lines {
+            stats.total_lines += 1;
+            scan_line(line, *line_number, config, args.verbose, &mut stats, &mut |mission, rejection| {
+                if rejection.is_none() {
+                    leaderboard.push(mission);
+                }
+            });
+            leaderboard.sort_by_key(|m| Reverse(m.duration));
+            leaderboard.truncate(args.top);
+        }
+
+        let deltas = leaderboard_deltas(&before, &leaderboard);
+        if !deltas.is_empty() {
+            match args.format {
+                OutputFormat::Ndjson => {
+                    for delta in &deltas {
+                        match serde_json::to_string(delta) {
+                            Ok(json) => println!("{}", json),
+                            Err(e) => eprintln!("Error serializing to JSON: {}", e),
+                        }
+                    }
+                }
+                OutputFormat::Json => print_json_output(&leaderboard, &stats),
+                _ => {
+                    println!("=== Leaderboard after line {} ===", stats.total_lines);
+                    print_default_output(&leaderboard, args.verbose, &stats);
+                }
+            }
+            let _ = io::stdout().flush();
+        }
+
+        if !batch.more {
+            thread::sleep(follow::POLL_INTERVAL);
         }
     }
 }
 
-fn print_json_output(missions: &[Mission], stats: &Statistics) {
-    let mission_outputs: Vec<MissionOutput> = missions
-        .iter()
-        .enumerate()
-        .map(|(idx, m)| MissionOutput {
-            rank: idx + 1,
-            date: m.date.clone(),
-            mission_id: m.mission_id.clone(),
-            destination: "Mars".to_string(),
-            status: "Completed".to_string(),
-            crew_size: m.crew_size,
-            duration_days: m.duration,
-            success_rate: m.success_rate,
-            security_code: m.security_code.clone(),
-            line_number: m.line_number,
-        })
-        .collect();
-
-    let output = JsonOutput {
-        statistics: Statistics {
-            total_lines: stats.total_lines,
-            data_lines: stats.data_lines,
-            mars_missions: stats.mars_missions,
-            completed_mars_missions: stats.completed_mars_missions,
-            valid_missions: stats.valid_missions,
-            errors: stats.errors,
-        },
-        missions: mission_outputs,
+/// Prints how each line matching `target` fared at every stage of the run
+/// described by `ctx`.
+fn run_explain(target: &str, file_path: &Path, ctx: &explain::Context, args: &Args) {
+    let source_lines: Vec<String> = match fs::read_to_string(file_path) {
+        Ok(text) => text.lines().map(str::to_string).collect(),
+        Err(e) => {
+            eprintln!("ERROR: Failed to read file: {}", e);
+            process::exit(1);
+        }
     };
 
-    match serde_json::to_string_pretty(&output) {
-        Ok(json) => println!("{}", json),
-        Err(e) => eprintln!("Error serializing to JSON: {}", e),
+    let line_numbers: Vec<usize> = match target.parse::<usize>() {
+        Ok(n) if (1..=source_lines.len()).contains(&n) => vec![n],
+        Ok(n) => {
+            eprintln!("ERROR: Line {} is out of range; the file has {} lines", n, source_lines.len());
+            process::exit(1);
+        }
+        Err(_) => ctx
+            .records
+            .iter()
+            .filter(|(m, _)| m.mission_id.eq_ignore_ascii_case(target))
+            .map(|(m, _)| m.line_number)
+            .collect(),
+    };
+    if line_numbers.is_empty() {
+        eprintln!("ERROR: No records found for mission ID {}", target);
+        process::exit(1);
     }
-}
 
-fn print_csv_output(missions: &[Mission]) {
-    println!("Rank,Date,Mission ID,Destination,Status,Crew Size,Duration (days),Success Rate,Security Code,Line Number");
+    let explanations: Vec<explain::Explanation> = line_numbers
+        .iter()
+        .map(|&n| explain::explain_line(n, &source_lines[n - 1], ctx))
+        .collect();
 
-    for (idx, mission) in missions.iter().enumerate() {
-        println!("{},{},{},Mars,Completed,{},{},{},{},{}",
-                 idx + 1,
-                 mission.date,
-                 mission.mission_id,
-                 mission.crew_size,
-                 mission.duration,
-                 mission.success_rate,
-                 mission.security_code,
-                 mission.line_number);
+    if args.format == OutputFormat::Default {
+        print!("{}", explain::render_text(&explanations));
+        return;
     }
+
+    tabular::print_tabular(
+        &Tabular {
+            title: "Explanation",
+            headers: &explain::HEADERS,
+            rows: &explain::rows(&explanations),
+            json: &explanations,
+            ndjson: &explanations,
+        },
+        args.format,
+        args.color,
+    );
 }
 
 fn main() {
     let args = Args::parse();
 
+    if args.ndjson_stats && args.format != OutputFormat::Ndjson {
+        eprintln!("ERROR: --ndjson-stats requires --format ndjson.");
+        process::exit(1);
+    }
+
+    // diff reserves 1 for "the logs differ"
+    let error_code = if matches!(args.command, Some(Command::Diff { .. })) { 2 } else { 1 };
+    let mut config = match &args.config {
+        Some(path) => match Config::load(path) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("ERROR: {}", e);
+                process::exit(error_code);
+            }
+        },
+        None => Config::default(),
+    };
+    if let Some(path) = &args.rules {
+        config.rules = match RuleSet::load(path) {
+            Ok(rules) => rules,
+            Err(e) => {
+                eprintln!("ERROR: {}", e);
+                process::exit(error_code);
+            }
+        };
+    }
+
+    match &args.command {
+        Some(Command::Stats { input_file, by }) => return run_stats(input_file, *by, &config, &args),
+        Some(Command::Histogram { input_file, buckets, bucket_width, quantiles }) => {
+            return run_histogram(input_file, *buckets, *bucket_width, *quantiles, &config, &args)
+        }
+        Some(Command::Trends { input_file, period, destination }) => {
+            return run_trends(input_file, *period, destination.as_deref(), &config, &args)
+        }
+        Some(Command::Anomalies { input_file, method, threshold }) => {
+            return run_anomalies(input_file, *method, *threshold, &config, &args)
+        }
+        Some(Command::Validate { input_file }) => return run_validate(input_file, &config, &args),
+        Some(Command::Index { input_file }) => return run_index(input_file),
+        Some(Command::Fmt { input_file, check, in_place }) => return run_fmt(input_file, *check, *in_place),
+        Some(Command::Diff { old_file, new_file, top }) => {
+            return run_diff(old_file, new_file, *top, &config, &args)
+        }
+        Some(Command::Merge { input_files, policy }) => return run_merge(input_files, *policy, &config, &args),
+        Some(Command::Dedupe { input_files, policy }) => return run_dedupe(input_files, *policy, &config, &args),
+        Some(Command::Show { mission_id, input_file }) => return run_show(mission_id, input_file, &config, &args),
+        Some(Command::Timeline { mission_id, input_file }) => {
+            return run_timeline(mission_id, input_file, &config, &args)
+        }
+        Some(Command::Repl { input_file }) => return run_repl(input_file, &config, &args),
+        Some(Command::Serve { input_files, port }) => return run_serve(input_files, *port, &config, &args),
+        Some(Command::Tui { input_file }) => {
+            if let Err(e) = tui::run(input_file, &config) {
+                eprintln!("ERROR: {}", e);
+                process::exit(1);
+            }
+            return;
+        }
+        None => {}
+    }
+
     // Check if input file is provided
-    let file_path = match args.input_file {
+    let file_path = match args.input_file.clone() {
         Some(path) => path,
         None => {
             eprintln!("ERROR: No input file provided.");
@@ -315,8 +1966,46 @@ fn main() {
         }
     };
 
-    // Process the file
-    let (mut missions, stats) = match process_file(&file_path, args.verbose) {
+    if args.follow {
+        return run_follow(&file_path, &config, &args);
+    }
+
+    // Keep rejected records as well when exporting, deduplicating, tracking
+    // mission lifecycles, or explaining them
+    let keep_records =
+        args.export_sqlite.is_some() || args.dedupe.is_some() || args.final_state || args.explain.is_some();
+    // Ranking every destination also keeps successful non-Mars missions
+    let all_destinations = args.group_by == Some(GroupKey::Destination);
+    let mut records = Vec::new();
+    let result = if let Some(state_path) = &args.state {
+        if args.format == OutputFormat::Html {
+            eprintln!("ERROR: --state keeps only the top missions, but the HTML report charts every one.");
+            process::exit(1);
+        }
+        checkpoint::settings_fingerprint(&[args.config.as_ref(), args.rules.as_ref()]).and_then(|settings| {
+            checkpoint::scan_incremental(&file_path, state_path, &config, settings, args.top, args.verbose)
+        })
+    } else if keep_records || all_destinations {
+        let mut missions = Vec::new();
+        scan_file(&file_path, &config, args.verbose, |m, r| {
+            let rankable = match r {
+                None => true,
+                Some(Rejection::NotMars) => all_destinations && m.is_successful(&config),
+                Some(_) => false,
+            };
+            if keep_records {
+                records.push((m.clone(), r));
+            }
+            if rankable {
+                missions.push(m);
+            }
+        })
+        .map(|stats| (missions, stats))
+    } else {
+        process_file(&file_path, &config, args.verbose)
+    };
+
+    let (mut missions, stats) = match result {
         Ok(result) => result,
         Err(e) => {
             eprintln!("ERROR: {}", e);
@@ -324,7 +2013,98 @@ fn main() {
         }
     };
 
+    if let Some(db_path) = &args.export_sqlite {
+        match sqlite::export(db_path, &file_path.display().to_string(), &records, &stats) {
+            Ok(run_id) => {
+                if args.verbose {
+                    eprintln!("Exported {} records to {} (run {})", records.len(), db_path.display(), run_id);
+                }
+            }
+            Err(e) => {
+                eprintln!("ERROR: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+
+    // Lines dropped by the filters below, with the reason, for --explain
+    let mut filtered: HashMap<usize, String> = HashMap::new();
+
+    if let Some(policy) = args.dedupe {
+        let parsed: Vec<Mission> = records.iter().map(|(m, _)| m.clone()).collect();
+        let merged = merge::merge(std::slice::from_ref(&parsed), policy);
+        let sources = [file_path.display().to_string()];
+        if merged.conflicts().next().is_some() {
+            print_conflicts(&merged, &sources);
+            eprintln!("ERROR: Conflicting duplicates found; choose a --dedupe policy other than report to resolve them.");
+            process::exit(1);
+        }
+        let kept: HashSet<usize> = merged.missions.iter().map(|(origin, _)| origin.line_number).collect();
+        missions.retain(|m| {
+            let keep = kept.contains(&m.line_number);
+            if !keep {
+                filtered.insert(m.line_number, format!("another record of {} kept by --dedupe {}", m.mission_id, policy.as_str()));
+            }
+            keep
+        });
+    }
+
+    if args.final_state {
+        let parsed: Vec<Mission> = records.iter().map(|(m, _)| m.clone()).collect();
+        let timelines = lifecycle::timelines(&parsed);
+        if args.verbose {
+            for timeline in &timelines {
+                for t in timeline.illegal_transitions() {
+                    eprintln!("Warning: Mission {} moves from {} to {} (line {} -> line {})",
+                             timeline.mission_id, t.from, t.to, t.from_line, t.to_line);
+                }
+            }
+        }
+        let finals: HashSet<usize> = timelines.iter().map(|t| t.final_state().line_number).collect();
+        missions.retain(|m| {
+            let keep = finals.contains(&m.line_number);
+            if !keep {
+                filtered.insert(m.line_number, format!("not the final state of {} (--final-state)", m.mission_id));
+            }
+            keep
+        });
+    }
+
+    if args.exclude_outliers {
+        let method = anomalies::Method::Mad;
+        let flagged = anomalies::detect(&missions, method, method.default_threshold());
+        if args.verbose {
+            for anomaly in &flagged {
+                eprintln!("Warning: Excluding line {} as an outlier: {}", anomaly.line_number, anomaly.reason);
+            }
+        }
+        for anomaly in &flagged {
+            filtered
+                .entry(anomaly.line_number)
+                .or_insert_with(|| format!("outlier: {} (--exclude-outliers)", anomaly.reason));
+        }
+        let lines: HashSet<usize> = flagged.iter().map(|a| a.line_number).collect();
+        missions.retain(|m| !lines.contains(&m.line_number));
+    }
+
+    if let Some(target) = &args.explain {
+        missions.sort_by_key(|m| Reverse(m.duration));
+        let ctx = explain::Context {
+            config: &config,
+            records: &records,
+            filtered: &filtered,
+            ranked: &missions,
+            top: args.top,
+            group_by: args.group_by,
+        };
+        return run_explain(target, &file_path, &ctx, &args);
+    }
+
     // Check if we found any valid missions
+    if missions.is_empty() && all_destinations {
+        eprintln!("ERROR: No valid completed missions found for any destination.");
+        process::exit(1);
+    }
     if missions.is_empty() {
         eprintln!("ERROR: No valid completed Mars missions found.");
         if stats.data_lines == 0 {
@@ -340,17 +2120,31 @@ fn main() {
     }
 
     // Sort missions by duration (descending)
-    missions.sort_by(|a, b| b.duration.cmp(&a.duration));
+    missions.sort_by_key(|m| Reverse(m.duration));
+
+    if let Some(key) = args.group_by {
+        print_grouped_output(&missions, key, args.top, &stats, &args);
+        return;
+    }
 
-    // Limit to top N
+    // Limit to top N (the HTML report still charts every valid mission)
     let num_to_show = args.top.min(missions.len());
-    missions.truncate(num_to_show);
+    let ranked = &missions[..num_to_show];
 
     // Output based on format
     match args.format {
-        OutputFormat::Default => print_default_output(&missions, args.verbose, &stats),
-        OutputFormat::Json => print_json_output(&missions, &stats),
-        OutputFormat::Csv => print_csv_output(&missions),
+        OutputFormat::Default => print_default_output(ranked, args.verbose, &stats),
+        OutputFormat::Json => print_json_output(ranked, &stats),
+        OutputFormat::Ndjson => print_ndjson_output(ranked, &stats, args.ndjson_stats),
+        OutputFormat::Csv => print_csv_output(ranked),
+        OutputFormat::Table => print_table_output(ranked, args.color),
+        OutputFormat::Markdown => print_markdown_output(ranked),
+        OutputFormat::Html => print!(
+            "{}",
+            report::render_html(&file_path.display().to_string(), ranked, &missions, &stats)
+        ),
+        OutputFormat::Parquet => tabular::write_binary(|out| columnar::write_parquet(out, ranked)),
+        OutputFormat::Arrow => tabular::write_binary(|out| columnar::write_arrow(out, ranked)),
     }
 }
 
@@ -562,6 +2356,46 @@ mod tests {
     }
 
     #[test]
+    fn test_write_ndjson_output() {
+        let missions = vec![
+            Mission::from_line("2045-07-12 | M1 | Mars | Completed | 5 | 500 | 98.7 | TRX-842-YHG", 1).unwrap(),
+            Mission::from_line("2046-07-12 | M2 | Mars | Completed | 4 | 300 | 91.0 | ABC-123-XYZ", 2).unwrap(),
+        ];
+        let stats = Statistics { valid_missions: 2, ..Default::default() };
+
+        let mut buf = Vec::new();
+        write_ndjson_output(&mut buf, &missions, &stats, true).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        let first: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(first["rank"], 1);
+        assert_eq!(first["mission_id"], "M1");
+        let last: serde_json::Value = serde_json::from_str(lines[2]).unwrap();
+        assert_eq!(last["statistics"]["valid_missions"], 2);
+
+        let mut buf = Vec::new();
+        write_ndjson_output(&mut buf, &missions, &stats, false).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap().lines().count(), 2);
+    }
+
+    #[test]
+    fn test_statistics_add_assign() {
+        let mut total = Statistics { total_lines: 3, warnings: 1, ..Default::default() };
+        let mut other = Statistics { total_lines: 4, valid_missions: 2, ..Default::default() };
+        other.rejections.rule_violations = 5;
+
+        total += &other;
+        total += &other;
+        assert_eq!(total.total_lines, 11);
+        assert_eq!(total.valid_missions, 4);
+        assert_eq!(total.warnings, 1);
+        assert_eq!(total.rejections.rule_violations, 10);
+    }
+
+    #[test]
+    #[allow(clippy::useless_vec, clippy::unnecessary_sort_by)]
     fn test_mission_sorting() {
         let mut missions = vec![
             Mission {
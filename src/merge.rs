@@ -0,0 +1,207 @@
+use crate::rules::Field;
+use crate::tabular::Cell;
+use crate::Mission;
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// How to pick one record when a mission ID appears more than once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Policy {
+    /// Keep the record with the latest date; ties go to the last one seen
+    LatestDate,
+    /// Keep the last record seen, in file and line order
+    LastSeen,
+    /// Keep the last Completed record, or the last record if none completed
+    PreferCompleted,
+    /// Keep identical duplicates once and refuse to pick between conflicting ones
+    Report,
+}
+
+impl Policy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Policy::LatestDate => "latest-date",
+            Policy::LastSeen => "last-seen",
+            Policy::PreferCompleted => "prefer-completed",
+            Policy::Report => "report",
+        }
+    }
+}
+
+/// Where a record came from: an index into the list of sources and a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Origin {
+    pub source: usize,
+    pub line_number: usize,
+}
+
+/// All records sharing one mission ID.
+#[derive(Debug, Clone, Serialize)]
+pub struct Duplicate {
+    pub mission_id: String,
+    pub occurrences: Vec<Origin>,
+    /// Fields whose values differ between the occurrences.
+    pub conflicting_fields: Vec<&'static str>,
+    /// The record kept, or `None` for an unresolved conflict.
+    pub kept: Option<Origin>,
+}
+
+pub struct Merged {
+    /// One record per mission ID, in order of first appearance.
+    pub missions: Vec<(Origin, Mission)>,
+    pub duplicates: Vec<Duplicate>,
+}
+
+impl Merged {
+    pub fn conflicts(&self) -> impl Iterator<Item = &Duplicate> {
+        self.duplicates.iter().filter(|d| d.kept.is_none())
+    }
+}
+
+fn date_of(mission: &Mission) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(&mission.date, "%Y-%m-%d").ok()
+}
+
+/// Index of the record `policy` keeps, or `None` for a conflict.
+fn choose(records: &[&(Origin, Mission)], policy: Policy, conflicting: bool) -> Option<usize> {
+    let last = records.len() - 1;
+    match policy {
+        Policy::LastSeen => Some(last),
+        // max_by_key returns the last maximum, so ties go to the last seen
+        Policy::LatestDate => records
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, (_, m))| date_of(m))
+            .map(|(idx, _)| idx),
+        Policy::PreferCompleted => records
+            .iter()
+            .rposition(|(_, m)| m.status.eq_ignore_ascii_case("completed"))
+            .or(Some(last)),
+        Policy::Report => (!conflicting).then_some(0),
+    }
+}
+
+/// Collapses `sources` (each a list of parsed missions, in order) into one
+/// record per mission ID. Conflicts under `Policy::Report` are left out of
+/// `missions` and reported with `kept: None`.
+pub fn merge(sources: &[Vec<Mission>], policy: Policy) -> Merged {
+    let records: Vec<(Origin, Mission)> = sources
+        .iter()
+        .enumerate()
+        .flat_map(|(source, missions)| {
+            missions
+                .iter()
+                .map(move |m| (Origin { source, line_number: m.line_number }, m.clone()))
+        })
+        .collect();
+
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    let mut groups: Vec<Vec<&(Origin, Mission)>> = Vec::new();
+    for record in &records {
+        let slot = *index.entry(record.1.mission_id.as_str()).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[slot].push(record);
+    }
+
+    let mut missions = Vec::with_capacity(groups.len());
+    let mut duplicates = Vec::new();
+
+    for group in groups {
+        if group.len() == 1 {
+            missions.push(group[0].clone());
+            continue;
+        }
+
+        let conflicting_fields: Vec<&'static str> = Field::ALL
+            .iter()
+            .filter(|f| group.iter().any(|(_, m)| f.text(m) != f.text(&group[0].1)))
+            .map(|f| f.as_str())
+            .collect();
+
+        let kept = choose(&group, policy, !conflicting_fields.is_empty());
+        if let Some(idx) = kept {
+            missions.push(group[idx].clone());
+        }
+
+        duplicates.push(Duplicate {
+            mission_id: group[0].1.mission_id.clone(),
+            occurrences: group.iter().map(|(origin, _)| *origin).collect(),
+            conflicting_fields,
+            kept: kept.map(|idx| group[idx].0),
+        });
+    }
+
+    Merged { missions, duplicates }
+}
+
+pub const HEADERS: [&str; 4] = ["Mission ID", "Occurrences", "Conflicting Fields", "Kept"];
+
+pub fn rows(duplicates: &[Duplicate], sources: &[String]) -> Vec<Vec<Cell>> {
+    let origin = |o: &Origin| format!("{}:{}", sources[o.source], o.line_number);
+    duplicates
+        .iter()
+        .map(|d| {
+            vec![
+                Cell::text(d.mission_id.as_str()),
+                Cell::text(d.occurrences.iter().map(origin).collect::<Vec<_>>().join(", ")),
+                Cell::text(d.conflicting_fields.join(", ")),
+                Cell::text(d.kept.as_ref().map(origin).unwrap_or_else(|| "conflict".to_string())),
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn missions(lines: &[&str]) -> Vec<Mission> {
+        lines
+            .iter()
+            .enumerate()
+            .map(|(idx, l)| Mission::from_line(l, idx + 1).unwrap())
+            .collect()
+    }
+
+    fn sources() -> Vec<Vec<Mission>> {
+        vec![
+            missions(&[
+                "2045-03-01 | A | Mars | Completed | 3 | 500 | 90.0 | ABC-123-XYZ",
+                "2045-01-01 | B | Mars | Completed | 3 | 400 | 90.0 | ABC-123-XYZ",
+            ]),
+            missions(&[
+                "2045-02-01 | A | Mars | Failed | 3 | 500 | 90.0 | ABC-123-XYZ",
+                "2045-01-01 | B | Mars | Completed | 3 | 400 | 90.0 | ABC-123-XYZ",
+                "2045-01-05 | C | Mars | Completed | 3 | 100 | 90.0 | ABC-123-XYZ",
+            ]),
+        ]
+    }
+
+    fn kept_status(merged: &Merged, id: &str) -> String {
+        merged.missions.iter().find(|(_, m)| m.mission_id == id).unwrap().1.status.clone()
+    }
+
+    #[test]
+    fn test_policies() {
+        let sources = sources();
+
+        assert_eq!(kept_status(&merge(&sources, Policy::LastSeen), "A"), "Failed");
+        assert_eq!(kept_status(&merge(&sources, Policy::LatestDate), "A"), "Completed");
+        assert_eq!(kept_status(&merge(&sources, Policy::PreferCompleted), "A"), "Completed");
+    }
+
+    #[test]
+    fn test_report_policy_only_refuses_real_conflicts() {
+        let merged = merge(&sources(), Policy::Report);
+        let ids: Vec<&str> = merged.missions.iter().map(|(_, m)| m.mission_id.as_str()).collect();
+
+        assert_eq!(ids, vec!["B", "C"]);
+        assert_eq!(merged.duplicates.len(), 2);
+        assert_eq!(merged.duplicates[0].conflicting_fields, vec!["date", "status"]);
+        assert_eq!(merged.conflicts().count(), 1);
+        assert_eq!(merged.duplicates[1].kept, Some(Origin { source: 0, line_number: 2 }));
+    }
+}
@@ -0,0 +1,103 @@
+use crate::is_comment_or_metadata;
+use chrono::NaiveDate;
+
+/// Date spellings accepted on input; all are rewritten as `YYYY-MM-DD`.
+const DATE_FORMATS: [&str; 3] = ["%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d"];
+
+fn canonical_date(date: &str) -> String {
+    DATE_FORMATS
+        .iter()
+        .find_map(|f| NaiveDate::parse_from_str(date, f).ok())
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| date.to_string())
+}
+
+/// Capitalises each space-separated word: "in progress" -> "In Progress".
+pub fn title_case(value: &str) -> String {
+    value
+        .split_whitespace()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn canonical_integer(value: &str) -> String {
+    value.parse::<u32>().map(|n| n.to_string()).unwrap_or_else(|_| value.to_string())
+}
+
+/// Rewrites one data line: trimmed fields joined by `" | "`, title-cased
+/// destination and status, ISO dates, and integers without leading zeros.
+/// Fields that do not parse are only trimmed, so nothing is lost.
+pub fn canonical_line(line: &str) -> String {
+    line.split('|')
+        .map(str::trim)
+        .enumerate()
+        .map(|(idx, field)| match idx {
+            0 => canonical_date(field),
+            2 | 3 => title_case(field),
+            4 | 5 => canonical_integer(field),
+            _ => field.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join(" | ")
+}
+
+/// Canonical form of a whole log, plus the line numbers that changed.
+/// Comments and metadata lines are kept byte for byte.
+pub fn format_log(text: &str) -> (String, Vec<usize>) {
+    let mut out = String::with_capacity(text.len());
+    let mut changed = Vec::new();
+
+    for (idx, line) in text.lines().enumerate() {
+        let formatted = if is_comment_or_metadata(line) {
+            line.to_string()
+        } else {
+            canonical_line(line)
+        };
+        if formatted != line {
+            changed.push(idx + 1);
+        }
+        out.push_str(&formatted);
+        out.push('\n');
+    }
+
+    (out, changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_line() {
+        assert_eq!(
+            canonical_line("2045/1/5|TST-0001 |  mars|in progress  | 03 | 0500 | 95.50 |ABC-123-XYZ"),
+            "2045-01-05 | TST-0001 | Mars | In Progress | 3 | 500 | 95.50 | ABC-123-XYZ"
+        );
+    }
+
+    #[test]
+    fn test_canonical_line_keeps_unparseable_fields() {
+        assert_eq!(
+            canonical_line("someday | X |MARS| COMPLETED | many | 10 | 1 | abc"),
+            "someday | X | Mars | Completed | many | 10 | 1 | abc"
+        );
+    }
+
+    #[test]
+    fn test_format_log_preserves_comments() {
+        let text = "# Header  \nSYSTEM: v1\n\n2045-01-05 | A | Mars | Completed | 3 | 500 | 95.5 | ABC-123-XYZ\n2045-01-06|B|Mars|Completed|3|500|95.5|ABC-123-XYZ\n";
+        let (out, changed) = format_log(text);
+
+        assert_eq!(changed, vec![5]);
+        assert!(out.starts_with("# Header  \nSYSTEM: v1\n\n"));
+        assert!(out.ends_with("2045-01-06 | B | Mars | Completed | 3 | 500 | 95.5 | ABC-123-XYZ\n"));
+        assert_eq!(format_log(&out).1, Vec::<usize>::new());
+    }
+}
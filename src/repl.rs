@@ -0,0 +1,361 @@
+use crate::aggregate::GroupKey;
+use crate::rules::Field;
+use crate::{Mission, OutputFormat};
+use clap::ValueEnum;
+use rustyline::completion::{Completer, Pair};
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Helper};
+use std::cmp::Reverse;
+
+pub const HELP: &str = "\
+Commands:
+  top [N] [by FIELD] [where COND [and COND ...]]   rank missions, longest first by default
+  stats [by KEY] [where COND [and COND ...]]       summary statistics per group
+  show MISSION_ID                                  look up a mission by ID or prefix
+  format FORMAT                                    switch the output format
+  help                                             show this help
+  quit                                             leave (Ctrl-D works too)
+
+Conditions compare a field with =, !=, <, <=, >, or >=, e.g. destination=Jupiter,
+crew_size>=4, status=\"In Progress\". Text is compared without regard to case.
+Ranking considers missions that pass every check; without a destination
+condition, only Mars missions are ranked.
+";
+
+const COMMANDS: [&str; 6] = ["top", "stats", "show", "format", "help", "quit"];
+const KEYWORDS: [&str; 3] = ["by", "where", "and"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// One `field<op>value` filter from a `where` clause.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub field: Field,
+    op: Op,
+    value: String,
+}
+
+impl Condition {
+    fn parse(token: &str) -> Result<Condition, String> {
+        // Two-character operators first, so `<=` is not read as `<`
+        let ops = [("!=", Op::Ne), ("<=", Op::Le), (">=", Op::Ge), ("=", Op::Eq), ("<", Op::Lt), (">", Op::Gt)];
+        let (pos, symbol, op) = ops
+            .iter()
+            .filter_map(|&(symbol, op)| token.find(symbol).map(|pos| (pos, symbol, op)))
+            .min_by_key(|&(pos, symbol, _)| (pos, Reverse(symbol.len())))
+            .ok_or_else(|| format!("expected a condition like destination=Mars, found '{}'", token))?;
+
+        let field = parse_field(&token[..pos])?;
+        let value = token[pos + symbol.len()..].to_string();
+        if matches!(op, Op::Lt | Op::Le | Op::Gt | Op::Ge) && value.parse::<f64>().is_err() {
+            return Err(format!("'{}' needs a number to compare with", token));
+        }
+        Ok(Condition { field, op, value })
+    }
+
+    pub fn matches(&self, mission: &Mission) -> bool {
+        let text = self.field.text(mission);
+        match self.op {
+            Op::Eq => text.eq_ignore_ascii_case(&self.value),
+            Op::Ne => !text.eq_ignore_ascii_case(&self.value),
+            _ => {
+                let (Some(actual), Ok(limit)) = (self.field.number(mission), self.value.parse::<f64>()) else {
+                    return false;
+                };
+                match self.op {
+                    Op::Lt => actual < limit,
+                    Op::Le => actual <= limit,
+                    Op::Gt => actual > limit,
+                    _ => actual >= limit,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    Top { count: Option<usize>, by: Field, conditions: Vec<Condition> },
+    Stats { by: GroupKey, conditions: Vec<Condition> },
+    Show(String),
+    Format(OutputFormat),
+    Help,
+    Quit,
+}
+
+fn parse_field(name: &str) -> Result<Field, String> {
+    Field::ALL
+        .iter()
+        .copied()
+        .find(|f| f.as_str().eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            let names: Vec<&str> = Field::ALL.iter().map(|f| f.as_str()).collect();
+            format!("unknown field '{}'; expected one of {}", name, names.join(", "))
+        })
+}
+
+/// Splits on whitespace, keeping double-quoted text together and dropping
+/// the quotes.
+fn tokenize(line: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_token = false;
+    let mut quoted = false;
+
+    for c in line.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_token = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_token = true;
+            }
+        }
+    }
+    if quoted {
+        return Err("unterminated quote".to_string());
+    }
+    if in_token {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+/// Parses `where COND [and COND ...]`, which must run to the end of the line.
+fn parse_conditions(tokens: &[String]) -> Result<Vec<Condition>, String> {
+    let mut conditions = Vec::new();
+    for (idx, token) in tokens.iter().enumerate() {
+        if idx % 2 == 1 {
+            if !token.eq_ignore_ascii_case("and") {
+                return Err(format!("expected 'and' between conditions, found '{}'", token));
+            }
+        } else {
+            conditions.push(Condition::parse(token)?);
+        }
+    }
+    if tokens.is_empty() || tokens.len().is_multiple_of(2) {
+        return Err("expected a condition after 'where' or 'and'".to_string());
+    }
+    Ok(conditions)
+}
+
+/// Reads the optional `by X` and `where ...` clauses shared by `top` and
+/// `stats`.
+fn parse_clauses(mut rest: &[String]) -> Result<(Option<&str>, Vec<Condition>), String> {
+    let mut by = None;
+    if rest.first().is_some_and(|t| t.eq_ignore_ascii_case("by")) {
+        by = Some(rest.get(1).ok_or("expected a name after 'by'")?.as_str());
+        rest = &rest[2..];
+    }
+    let conditions = match rest.first() {
+        None => Vec::new(),
+        Some(t) if t.eq_ignore_ascii_case("where") => parse_conditions(&rest[1..])?,
+        Some(t) => return Err(format!("unexpected '{}'", t)),
+    };
+    Ok((by, conditions))
+}
+
+/// Parses one REPL line; `None` for a blank line.
+pub fn parse(line: &str) -> Result<Option<Query>, String> {
+    let tokens = tokenize(line)?;
+    let Some((command, rest)) = tokens.split_first() else {
+        return Ok(None);
+    };
+
+    let query = match command.to_ascii_lowercase().as_str() {
+        "top" => {
+            let (count, rest) = match rest.first().and_then(|t| t.parse::<usize>().ok()) {
+                Some(n) => (Some(n), &rest[1..]),
+                None => (None, rest),
+            };
+            let (by, conditions) = parse_clauses(rest)?;
+            let by = match by {
+                Some(name) => parse_field(name)?,
+                None => Field::Duration,
+            };
+            if !matches!(by, Field::CrewSize | Field::Duration | Field::SuccessRate) {
+                return Err("missions can be ranked by crew_size, duration, or success_rate".to_string());
+            }
+            Query::Top { count, by, conditions }
+        }
+        "stats" => {
+            let (by, conditions) = parse_clauses(rest)?;
+            let by = match by {
+                Some(name) => GroupKey::from_str(name, true)
+                    .map_err(|_| format!("cannot group by '{}'; expected one of {}", name, value_names::<GroupKey>()))?,
+                None => GroupKey::Destination,
+            };
+            Query::Stats { by, conditions }
+        }
+        "show" => match rest {
+            [id] => Query::Show(id.clone()),
+            _ => return Err("usage: show MISSION_ID".to_string()),
+        },
+        "format" => match rest {
+            [name] => {
+                let format = OutputFormat::from_str(name, true)
+                    .map_err(|_| format!("unknown format '{}'; expected one of {}", name, text_formats().join(", ")))?;
+                if !text_formats().contains(&name.to_ascii_lowercase().as_str()) {
+                    return Err(format!("the REPL prints text only; expected one of {}", text_formats().join(", ")));
+                }
+                Query::Format(format)
+            }
+            _ => return Err("usage: format FORMAT".to_string()),
+        },
+        "help" | "?" => Query::Help,
+        "quit" | "exit" => Query::Quit,
+        other => return Err(format!("unknown command '{}'; type 'help' for a list", other)),
+    };
+    Ok(Some(query))
+}
+
+fn value_names<T: ValueEnum>() -> String {
+    T::value_variants()
+        .iter()
+        .filter_map(|v| v.to_possible_value())
+        .map(|v| v.get_name().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Formats the REPL can print to a terminal.
+pub fn text_formats() -> Vec<&'static str> {
+    vec!["default", "json", "ndjson", "csv", "table", "markdown"]
+}
+
+/// Tab completion for commands, keywords, field names, group keys, and
+/// formats.
+pub struct ReplHelper;
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let start = line[..pos].rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let word = &line[start..pos];
+        if word.contains(['=', '<', '>', '!']) {
+            return Ok((start, Vec::new()));
+        }
+
+        let words: Vec<String> = if start == 0 {
+            COMMANDS.iter().map(|c| c.to_string()).collect()
+        } else if line[..start].trim_end().ends_with("format") {
+            text_formats().iter().map(|f| f.to_string()).collect()
+        } else {
+            let group_keys = GroupKey::value_variants().iter().map(|k| k.as_str());
+            KEYWORDS
+                .iter()
+                .copied()
+                .chain(Field::ALL.iter().map(|f| f.as_str()))
+                .chain(group_keys)
+                .map(str::to_string)
+                .collect()
+        };
+
+        let mut candidates: Vec<Pair> = words
+            .into_iter()
+            .filter(|w| w.starts_with(&word.to_ascii_lowercase()))
+            .map(|w| Pair { display: w.clone(), replacement: w })
+            .collect();
+        candidates.sort_by(|a, b| a.display.cmp(&b.display));
+        candidates.dedup_by(|a, b| a.display == b.display);
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustyline::history::DefaultHistory;
+
+    fn mission() -> Mission {
+        Mission::from_line("2045-01-01 | JUP-0001 | Jupiter | In Progress | 4 | 300 | 90.5 | ABC-123-XYZ", 1).unwrap()
+    }
+
+    #[test]
+    fn test_parse_top() {
+        let query = parse("top 10 by success_rate where destination=Jupiter and crew_size>=4").unwrap().unwrap();
+        let Query::Top { count, by, conditions } = query else { panic!("expected top") };
+
+        assert_eq!(count, Some(10));
+        assert_eq!(by, Field::SuccessRate);
+        assert_eq!(conditions.len(), 2);
+        assert!(conditions.iter().all(|c| c.matches(&mission())));
+    }
+
+    #[test]
+    fn test_parse_other_commands() {
+        assert_eq!(parse("   ").unwrap(), None);
+        assert_eq!(parse("show KLM-1234").unwrap(), Some(Query::Show("KLM-1234".into())));
+        assert_eq!(parse("format JSON").unwrap(), Some(Query::Format(OutputFormat::Json)));
+        assert!(matches!(parse("stats by year").unwrap(), Some(Query::Stats { by: GroupKey::Year, .. })));
+        assert!(matches!(parse("stats").unwrap(), Some(Query::Stats { by: GroupKey::Destination, .. })));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("top by security_code").unwrap_err().contains("ranked by"));
+        assert!(parse("top where").unwrap_err().contains("expected a condition"));
+        assert!(parse("top where weight=3").unwrap_err().contains("unknown field"));
+        assert!(parse("top where crew_size>many").unwrap_err().contains("needs a number"));
+        assert!(parse("format parquet").unwrap_err().contains("text only"));
+        assert!(parse("launch").unwrap_err().contains("unknown command"));
+    }
+
+    #[test]
+    fn test_conditions() {
+        let m = mission();
+        let check = |line: &str| {
+            let Some(Query::Top { conditions, .. }) = parse(line).unwrap() else { panic!() };
+            conditions[0].matches(&m)
+        };
+
+        assert!(check("top where status=\"in progress\""));
+        assert!(check("top where destination!=Mars"));
+        assert!(check("top where success_rate<=90.5"));
+        assert!(!check("top where duration<300"));
+    }
+
+    #[test]
+    fn test_completion() {
+        let history = DefaultHistory::new();
+        let ctx = Context::new(&history);
+        let complete = |line: &str| {
+            let (start, pairs) = ReplHelper.complete(line, line.len(), &ctx).unwrap();
+            (start, pairs.into_iter().map(|p| p.replacement).collect::<Vec<_>>())
+        };
+
+        assert_eq!(complete("st"), (0, vec!["stats".to_string()]));
+        assert_eq!(complete("top 5 by su"), (9, vec!["success_rate".to_string()]));
+        assert_eq!(complete("format nd"), (7, vec!["ndjson".to_string()]));
+        assert_eq!(complete("top where crew_size=").1, Vec::<String>::new());
+    }
+}
@@ -0,0 +1,220 @@
+use crate::{histogram, Mission, Statistics};
+use std::fmt::Write;
+
+const CHART_WIDTH: usize = 640;
+const CHART_HEIGHT: usize = 220;
+const DURATION_BUCKETS: usize = 10;
+
+const STYLE: &str = "\
+body { font-family: -apple-system, 'Segoe UI', Helvetica, Arial, sans-serif; margin: 2em auto; max-width: 1100px; color: #222; }
+h1 { font-size: 1.6em; margin-bottom: 0.2em; }
+h2 { font-size: 1.2em; margin-top: 2em; border-bottom: 1px solid #ddd; padding-bottom: 0.3em; }
+table { border-collapse: collapse; font-size: 0.9em; }
+th, td { padding: 4px 10px; border-bottom: 1px solid #eee; text-align: left; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+th { background: #f6f6f6; }
+.meta { color: #777; font-size: 0.9em; }
+svg text { font-size: 11px; fill: #444; }
+";
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Vertical bar chart with one labelled bar per entry.
+fn svg_bar_chart(title: &str, bars: &[(String, usize)], color: &str) -> String {
+    let mut svg = String::new();
+    let max = bars.iter().map(|(_, v)| *v).max().unwrap_or(0).max(1);
+    let plot_height = CHART_HEIGHT - 50;
+    let slot = CHART_WIDTH / bars.len().max(1);
+    let bar_width = slot.saturating_sub(6).max(1);
+
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" role="img" aria-label="{t}">"#,
+        w = CHART_WIDTH,
+        h = CHART_HEIGHT,
+        t = escape(title)
+    );
+
+    for (idx, (label, value)) in bars.iter().enumerate() {
+        let height = value * plot_height / max;
+        let x = idx * slot + 3;
+        let y = 20 + plot_height - height;
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{x}" y="{y}" width="{bar_width}" height="{height}" fill="{color}"><title>{label}: {value}</title></rect>"#,
+            label = escape(label)
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{cx}" y="{vy}" text-anchor="middle">{value}</text>"#,
+            cx = x + bar_width / 2,
+            vy = y.saturating_sub(4).max(10)
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{cx}" y="{ly}" text-anchor="middle">{label}</text>"#,
+            cx = x + bar_width / 2,
+            ly = CHART_HEIGHT - 12,
+            label = escape(label)
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn rejection_breakdown(stats: &Statistics) -> Vec<(String, usize)> {
+    let parsed = stats.data_lines.saturating_sub(stats.rejections.invalid_format);
+    vec![
+        ("Read error".to_string(), stats.rejections.read_errors),
+        ("Invalid format".to_string(), stats.rejections.invalid_format),
+        ("Not Mars".to_string(), parsed.saturating_sub(stats.mars_missions)),
+        (
+            "Not completed".to_string(),
+            stats.mars_missions.saturating_sub(stats.completed_mars_missions),
+        ),
+        ("Zero duration".to_string(), stats.rejections.zero_duration),
+        ("Bad security code".to_string(), stats.rejections.invalid_security_code),
+        ("Bad mission ID".to_string(), stats.rejections.invalid_mission_id),
+        ("Crew size range".to_string(), stats.rejections.crew_size_out_of_range),
+        ("Duration range".to_string(), stats.rejections.duration_out_of_range),
+        ("Success rate range".to_string(), stats.rejections.success_rate_out_of_range),
+        ("Rule violation".to_string(), stats.rejections.rule_violations),
+    ]
+}
+
+fn key_value_table(out: &mut String, rows: &[(&str, usize)]) {
+    out.push_str("<table>\n");
+    for (label, value) in rows {
+        let _ = writeln!(out, r#"<tr><th>{}</th><td class="num">{}</td></tr>"#, label, value);
+    }
+    out.push_str("</table>\n");
+}
+
+fn page_start(out: &mut String, title: &str) {
+    out.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    let _ = writeln!(out, "<title>{}</title>", escape(title));
+    let _ = writeln!(out, "<style>\n{}</style>\n</head>\n<body>", STYLE);
+}
+
+/// Renders a standalone page holding a single table, for analysis modes
+/// other than the mission ranking.
+pub fn render_html_table(title: &str, headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut out = String::new();
+    page_start(&mut out, title);
+    let _ = writeln!(out, "<h1>{}</h1>", escape(title));
+
+    out.push_str("<table>\n<tr>");
+    for header in headers {
+        let _ = write!(out, "<th>{}</th>", escape(header));
+    }
+    out.push_str("</tr>\n");
+
+    for row in rows {
+        out.push_str("<tr>");
+        for cell in row {
+            if cell.parse::<f64>().is_ok() {
+                let _ = write!(out, r#"<td class="num">{}</td>"#, escape(cell));
+            } else {
+                let _ = write!(out, "<td>{}</td>", escape(cell));
+            }
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>\n</body>\n</html>\n");
+
+    out
+}
+
+/// Renders a standalone HTML page with no external assets.
+pub fn render_html(source: &str, ranked: &[Mission], all: &[Mission], stats: &Statistics) -> String {
+    let mut out = String::new();
+
+    page_start(&mut out, &format!("Mars Mission Report: {}", source));
+    out.push_str("<h1>Mars Mission Report</h1>\n");
+    let _ = writeln!(
+        out,
+        r#"<p class="meta">Source: {} &middot; {} valid completed Mars mission{}</p>"#,
+        escape(source),
+        all.len(),
+        if all.len() == 1 { "" } else { "s" }
+    );
+
+    out.push_str("<h2>Processing Statistics</h2>\n");
+    key_value_table(
+        &mut out,
+        &[
+            ("Total lines processed", stats.total_lines),
+            ("Data lines", stats.data_lines),
+            ("Total Mars missions", stats.mars_missions),
+            ("Completed Mars missions", stats.completed_mars_missions),
+            ("Valid missions stored", stats.valid_missions),
+            ("Errors/warnings", stats.errors),
+        ],
+    );
+
+    out.push_str("<h2>Rejection Breakdown</h2>\n");
+    let breakdown = rejection_breakdown(stats);
+    let rows: Vec<(&str, usize)> = breakdown.iter().map(|(l, v)| (l.as_str(), *v)).collect();
+    key_value_table(&mut out, &rows);
+    out.push_str(&svg_bar_chart("Rejected lines by reason", &breakdown, "#c0504d"));
+
+    out.push_str("<h2>Duration Distribution</h2>\n");
+    let durations: Vec<u32> = all.iter().map(|m| m.duration).collect();
+    let buckets: Vec<(String, usize)> = histogram::by_bucket_count(&durations, DURATION_BUCKETS)
+        .into_iter()
+        .map(|b| (format!("{}-{}", b.lower, b.upper), b.count))
+        .collect();
+    out.push_str(&svg_bar_chart("Mission duration in days", &buckets, "#4f81bd"));
+
+    let _ = writeln!(
+        out,
+        "<h2>Top {} Mission{}</h2>",
+        ranked.len(),
+        if ranked.len() == 1 { "" } else { "s" }
+    );
+    out.push_str("<table>\n<tr><th>Rank</th><th>Date</th><th>Mission ID</th><th>Destination</th><th>Status</th><th>Crew Size</th><th>Duration (days)</th><th>Success Rate</th><th>Security Code</th><th>Line Number</th></tr>\n");
+    for (idx, mission) in ranked.iter().enumerate() {
+        let m = mission.to_output(idx + 1);
+        let _ = writeln!(
+            out,
+            r#"<tr><td class="num">{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class="num">{}</td><td class="num">{}</td><td class="num">{}</td><td><code>{}</code></td><td class="num">{}</td></tr>"#,
+            m.rank,
+            escape(&m.date),
+            escape(&m.mission_id),
+            escape(&m.destination),
+            escape(&m.status),
+            m.crew_size,
+            m.duration_days,
+            m.success_rate,
+            escape(&m.security_code),
+            m.line_number
+        );
+    }
+    out.push_str("</table>\n</body>\n</html>\n");
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("<a href=\"x\">&'</a>"), "&lt;a href=&quot;x&quot;&gt;&amp;&#39;&lt;/a&gt;");
+    }
+}
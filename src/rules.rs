@@ -0,0 +1,419 @@
+use crate::Mission;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    Date,
+    MissionId,
+    Destination,
+    Status,
+    CrewSize,
+    Duration,
+    SuccessRate,
+    SecurityCode,
+}
+
+impl Field {
+    /// Every field, in log column order.
+    pub const ALL: [Field; 8] = [
+        Field::Date,
+        Field::MissionId,
+        Field::Destination,
+        Field::Status,
+        Field::CrewSize,
+        Field::Duration,
+        Field::SuccessRate,
+        Field::SecurityCode,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Field::Date => "date",
+            Field::MissionId => "mission_id",
+            Field::Destination => "destination",
+            Field::Status => "status",
+            Field::CrewSize => "crew_size",
+            Field::Duration => "duration",
+            Field::SuccessRate => "success_rate",
+            Field::SecurityCode => "security_code",
+        }
+    }
+
+    pub fn text(self, mission: &Mission) -> String {
+        match self {
+            Field::Date => mission.date.clone(),
+            Field::MissionId => mission.mission_id.clone(),
+            Field::Destination => mission.destination.clone(),
+            Field::Status => mission.status.clone(),
+            Field::CrewSize => mission.crew_size.to_string(),
+            Field::Duration => mission.duration.to_string(),
+            Field::SuccessRate => mission.success_rate.to_string(),
+            Field::SecurityCode => mission.security_code.clone(),
+        }
+    }
+
+    pub fn number(self, mission: &Mission) -> Option<f64> {
+        match self {
+            Field::CrewSize => Some(mission.crew_size as f64),
+            Field::Duration => Some(mission.duration as f64),
+            Field::SuccessRate => Some(mission.success_rate),
+            _ => self.text(mission).parse().ok(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Error,
+    #[serde(alias = "warning")]
+    Warn,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warn => "warn",
+        }
+    }
+}
+
+/// Constraints on one field, as written in a rule's `when` table.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawCheck {
+    field: Option<Field>,
+    #[serde(default)]
+    required: bool,
+    pattern: Option<String>,
+    allowed: Option<Vec<String>>,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRule {
+    name: String,
+    /// `false` drops a default rule of the same name.
+    #[serde(default = "enabled")]
+    enabled: bool,
+    #[serde(default)]
+    severity: Severity,
+    message: Option<String>,
+    when: Option<RawCheck>,
+    field: Option<Field>,
+    #[serde(default)]
+    required: bool,
+    pattern: Option<String>,
+    allowed: Option<Vec<String>>,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+impl RawRule {
+    fn check(&mut self) -> RawCheck {
+        RawCheck {
+            field: self.field,
+            required: self.required,
+            pattern: self.pattern.take(),
+            allowed: self.allowed.take(),
+            min: self.min,
+            max: self.max,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRuleSet {
+    #[serde(default)]
+    rules: Vec<RawRule>,
+}
+
+#[derive(Debug, Clone)]
+struct Check {
+    field: Field,
+    required: bool,
+    pattern: Option<Regex>,
+    allowed: Option<Vec<String>>,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+impl Check {
+    fn compile(raw: RawCheck, rule: &str) -> Result<Self, String> {
+        let field = raw
+            .field
+            .ok_or_else(|| format!("rule '{}' is missing `field`", rule))?;
+        let pattern = match raw.pattern {
+            Some(p) => Some(
+                Regex::new(&p).map_err(|e| format!("rule '{}' has an invalid pattern: {}", rule, e))?,
+            ),
+            None => None,
+        };
+        Ok(Check { field, required: raw.required, pattern, allowed: raw.allowed, min: raw.min, max: raw.max })
+    }
+
+    /// Why `mission` fails this check, or `None` if it passes. Empty
+    /// optional values pass every constraint.
+    fn failure(&self, mission: &Mission) -> Option<String> {
+        let name = self.field.as_str();
+        let text = self.field.text(mission);
+
+        if text.is_empty() {
+            return self.required.then(|| format!("{} is required", name));
+        }
+
+        if let Some(pattern) = &self.pattern {
+            if !pattern.is_match(&text) {
+                return Some(format!("{} '{}' does not match {}", name, text, pattern.as_str()));
+            }
+        }
+
+        if let Some(allowed) = &self.allowed {
+            if !allowed.iter().any(|a| a.eq_ignore_ascii_case(&text)) {
+                return Some(format!("{} '{}' is not one of {}", name, text, allowed.join(", ")));
+            }
+        }
+
+        if self.min.is_some() || self.max.is_some() {
+            let value = match self.field.number(mission) {
+                Some(v) => v,
+                None => return Some(format!("{} '{}' is not a number", name, text)),
+            };
+            if let Some(min) = self.min.filter(|&min| value < min) {
+                return Some(format!("{} {} is below {}", name, value, min));
+            }
+            if let Some(max) = self.max.filter(|&max| value > max) {
+                return Some(format!("{} {} is above {}", name, value, max));
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub name: String,
+    pub severity: Severity,
+    message: Option<String>,
+    when: Option<Check>,
+    check: Check,
+}
+
+/// A failed rule for one mission.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub rule: String,
+    pub severity: Severity,
+    pub field: &'static str,
+    pub message: String,
+}
+
+/// The built-in record checks. Their names match the rejection counters
+/// they feed, and a `--rules` file may replace or disable them by name.
+const DEFAULT_RULES: &str = r#"
+[[rules]]
+name = "zero_duration"
+when = { field = "status", allowed = ["Completed"] }
+field = "duration"
+min = 1
+message = "completed mission has a duration of 0 days"
+
+[[rules]]
+name = "invalid_security_code"
+field = "security_code"
+required = true
+pattern = '^[A-Z]{3}-[0-9]{3}-[A-Z]{3}$'
+"#;
+
+/// The default rules, overlaid with any loaded from `--rules`. Every
+/// parsed mission is checked against them, before the destination and
+/// status filters.
+#[derive(Debug, Clone)]
+pub struct RuleSet {
+    pub rules: Vec<Rule>,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self::parse("").expect("default rules are valid")
+    }
+}
+
+impl RuleSet {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read rules file {}: {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| format!("Invalid rules file {}: {}", path.display(), e))
+    }
+
+    /// Overlays the rules in `text` on the defaults: a rule named like a
+    /// default replaces it (or drops it, with `enabled = false`), and any
+    /// other rule is appended.
+    fn parse(text: &str) -> Result<Self, String> {
+        let defaults: RawRuleSet = toml::from_str(DEFAULT_RULES).map_err(|e| e.to_string())?;
+        let raw: RawRuleSet = toml::from_str(text).map_err(|e| e.to_string())?;
+
+        let mut merged = defaults.rules;
+        for rule in raw.rules {
+            match merged.iter().position(|r| r.name == rule.name) {
+                Some(idx) => merged[idx] = rule,
+                None => merged.push(rule),
+            }
+        }
+
+        let rules = merged
+            .into_iter()
+            .filter(|r| r.enabled)
+            .map(|mut r| {
+                Ok(Rule {
+                    check: Check::compile(r.check(), &r.name)?,
+                    when: r.when.take().map(|w| Check::compile(w, &r.name)).transpose()?,
+                    name: r.name,
+                    severity: r.severity,
+                    message: r.message,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(RuleSet { rules })
+    }
+
+    /// Every rule `mission` fails, defaults first, then in file order. A rule with a `when`
+    /// condition only applies to missions that satisfy the condition.
+    pub fn check(&self, mission: &Mission) -> Vec<Violation> {
+        self.rules
+            .iter()
+            .filter(|r| r.when.as_ref().is_none_or(|w| w.failure(mission).is_none()))
+            .filter_map(|r| {
+                r.check.failure(mission).map(|reason| Violation {
+                    rule: r.name.clone(),
+                    severity: r.severity,
+                    field: r.check.field.as_str(),
+                    message: r.message.clone().unwrap_or(reason),
+                })
+            })
+            .collect()
+    }
+
+    pub fn has_errors(&self, mission: &Mission) -> bool {
+        self.check(mission).iter().any(|v| v.severity == Severity::Error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &str = r#"
+[[rules]]
+name = "known-destination"
+field = "destination"
+allowed = ["Mars", "Moon"]
+
+[[rules]]
+name = "mission-id-format"
+field = "mission_id"
+pattern = '^[A-Z]+-\d+$'
+severity = "warn"
+
+[[rules]]
+name = "completed-has-crew"
+when = { field = "status", allowed = ["Completed"] }
+field = "crew_size"
+min = 2
+message = "completed missions need at least two crew"
+"#;
+
+    fn mission(line: &str) -> Mission {
+        Mission::from_line(line, 1).unwrap()
+    }
+
+    #[test]
+    fn test_rules_pass() {
+        let rules = RuleSet::parse(RULES).unwrap();
+        let m = mission("2045-01-01 | MRS-1 | mars | Completed | 3 | 100 | 90.0 | ABC-123-XYZ");
+        assert!(rules.check(&m).is_empty());
+    }
+
+    #[test]
+    fn test_rules_report_every_failure() {
+        let rules = RuleSet::parse(RULES).unwrap();
+        let m = mission("2045-01-01 | odd | Pluto | Completed | 1 | 100 | 90.0 | ABC-123-XYZ");
+        let violations = rules.check(&m);
+
+        assert_eq!(violations.len(), 3);
+        assert_eq!(violations[0].rule, "known-destination");
+        assert_eq!(violations[0].message, "destination 'Pluto' is not one of Mars, Moon");
+        assert_eq!(violations[1].severity, Severity::Warn);
+        assert_eq!(violations[2].message, "completed missions need at least two crew");
+        assert!(rules.has_errors(&m));
+    }
+
+    #[test]
+    fn test_when_condition_limits_rule() {
+        let rules = RuleSet::parse(RULES).unwrap();
+        let m = mission("2045-01-01 | MRS-1 | Mars | Failed | 1 | 100 | 90.0 | ABC-123-XYZ");
+        assert!(rules.check(&m).is_empty());
+    }
+
+    #[test]
+    fn test_required_field() {
+        let rules = RuleSet::parse("[[rules]]\nname = \"id\"\nfield = \"mission_id\"\nrequired = true\n").unwrap();
+        let m = mission("2045-01-01 |  | Mars | Completed | 1 | 100 | 90.0 | ABC-123-XYZ");
+        assert_eq!(rules.check(&m)[0].message, "mission_id is required");
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        assert!(RuleSet::parse("[[rules]]\nname = \"x\"\nfield = \"crew\"\n").is_err());
+        assert!(RuleSet::parse("[[rules]]\nname = \"x\"\nfield = \"date\"\npattern = \"(\"\n").is_err());
+        assert!(RuleSet::parse("[[rules]]\nname = \"x\"\n").unwrap_err().contains("missing `field`"));
+        assert_eq!(RuleSet::parse("").unwrap().rules.len(), 2);
+    }
+
+    #[test]
+    fn test_default_rules() {
+        let rules = RuleSet::default();
+        let m = mission("2045-01-01 | MRS-1 | Moon | Completed | 3 | 0 | 90.0 | abc");
+        let names: Vec<String> = rules.check(&m).into_iter().map(|v| v.rule).collect();
+        assert_eq!(names, ["zero_duration", "invalid_security_code"]);
+    }
+
+    #[test]
+    fn test_override_and_disable_default_rules() {
+        let rules = RuleSet::parse(
+            r#"
+[[rules]]
+name = "zero_duration"
+enabled = false
+
+[[rules]]
+name = "invalid_security_code"
+field = "security_code"
+pattern = '^[A-Z]{3}-[0-9]{3}-[A-Z]{2,3}$'
+severity = "warn"
+"#,
+        )
+        .unwrap();
+        let m = mission("2045-01-01 | MRS-1 | Mars | Completed | 3 | 0 | 90.0 | ABC-123-XY");
+        assert!(rules.check(&m).is_empty());
+
+        let m = mission("2045-01-01 | MRS-1 | Mars | Completed | 3 | 0 | 90.0 | bad");
+        assert_eq!(rules.check(&m)[0].severity, Severity::Warn);
+        assert!(!rules.has_errors(&m));
+    }
+}
@@ -0,0 +1,387 @@
+use crate::lookup::Found;
+use crate::rules::Field;
+use crate::validation::Config;
+use crate::{scan_file, JsonOutput, Mission, Rejection, Statistics};
+use serde::Serialize;
+use serde_json::json;
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use tiny_http::{Header, Method, Response, Server};
+
+/// One log being served, rescanned whenever its size or mtime changes.
+struct Source {
+    path: PathBuf,
+    version: Option<(u64, SystemTime)>,
+    records: Vec<(Mission, Option<Rejection>)>,
+    stats: Statistics,
+}
+
+fn version_of(path: &PathBuf) -> Option<(u64, SystemTime)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()?))
+}
+
+/// The logs behind the API and the answers computed from them.
+pub struct Catalog<'a> {
+    config: &'a Config,
+    verbose: bool,
+    sources: Vec<Source>,
+}
+
+/// An HTTP status and the JSON body to send with it.
+#[derive(Debug)]
+pub struct Reply {
+    pub status: u16,
+    pub body: serde_json::Value,
+}
+
+impl Reply {
+    fn ok<T: Serialize>(body: &T) -> Reply {
+        match serde_json::to_value(body) {
+            Ok(body) => Reply { status: 200, body },
+            Err(e) => Reply::error(500, format!("Error serializing to JSON: {}", e)),
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Reply {
+        Reply { status, body: json!({ "error": message.into() }) }
+    }
+}
+
+/// Splits `a=1&b=two%20words` into decoded pairs.
+fn query_pairs(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let hex = bytes.get(idx + 1..idx + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[idx], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                idx += 2;
+            }
+            (b'+', _) => out.push(b' '),
+            (b, _) => out.push(b),
+        }
+        idx += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+impl<'a> Catalog<'a> {
+    /// Scans every log once; a log that cannot be read is an error here,
+    /// while later reload failures keep serving the last good scan.
+    pub fn load(paths: &[PathBuf], config: &'a Config, verbose: bool) -> Result<Catalog<'a>, String> {
+        let mut catalog = Catalog {
+            config,
+            verbose,
+            sources: paths
+                .iter()
+                .map(|path| Source { path: path.clone(), version: None, records: Vec::new(), stats: Statistics::default() })
+                .collect(),
+        };
+        for idx in 0..catalog.sources.len() {
+            catalog.rescan(idx)?;
+        }
+        Ok(catalog)
+    }
+
+    fn rescan(&mut self, idx: usize) -> Result<(), String> {
+        let source = &mut self.sources[idx];
+        let version = version_of(&source.path);
+        let mut records = Vec::new();
+        let stats = scan_file(&source.path, self.config, self.verbose, |m, r| records.push((m, r)))
+            .map_err(|e| format!("{}: {}", source.path.display(), e))?;
+        source.version = version;
+        source.records = records;
+        source.stats = stats;
+        Ok(())
+    }
+
+    /// Rescans logs whose size or modification time changed.
+    pub fn reload_changed(&mut self) {
+        for idx in 0..self.sources.len() {
+            if version_of(&self.sources[idx].path) == self.sources[idx].version {
+                continue;
+            }
+            if self.verbose {
+                eprintln!("Reloading {}", self.sources[idx].path.display());
+            }
+            if let Err(e) = self.rescan(idx) {
+                eprintln!("Warning: Keeping the previous contents of {}", e);
+            }
+        }
+    }
+
+    fn statistics(&self) -> Statistics {
+        let mut total = Statistics::default();
+        for source in &self.sources {
+            total += &source.stats;
+        }
+        total
+    }
+
+    /// Answers one request for `url` (path and query string).
+    pub fn handle(&self, method: &str, url: &str) -> Reply {
+        if method != "GET" {
+            return Reply::error(405, "only GET is supported");
+        }
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let path = path.trim_end_matches('/');
+
+        match path {
+            "/stats" => Reply::ok(&self.statistics()),
+            "/missions" => match self.missions(&query_pairs(query)) {
+                Ok(output) => Reply::ok(&output),
+                Err(e) => Reply::error(400, e),
+            },
+            _ => match path.strip_prefix("/missions/") {
+                Some(id) if !id.is_empty() => self.mission(&percent_decode(id)),
+                _ => Reply::error(404, format!("no endpoint {}; try /missions, /missions/{{id}}, or /stats", path)),
+            },
+        }
+    }
+
+    /// `/missions`: missions that pass every check, ranked like the CLI.
+    /// A `destination` filter opens the ranking to successful missions
+    /// anywhere, as `--group-by destination` does.
+    fn missions(&self, params: &[(String, String)]) -> Result<JsonOutput, String> {
+        let mut filters: Vec<(Field, String)> = Vec::new();
+        let mut top = None;
+        let mut sort = Field::Duration;
+        let mut descending = true;
+
+        for (key, value) in params {
+            match key.as_str() {
+                "top" => top = Some(value.parse::<usize>().map_err(|_| format!("top must be a number, not '{}'", value))?),
+                "sort" => sort = field_named(value)?,
+                "order" => {
+                    descending = match value.as_str() {
+                        "asc" => false,
+                        "desc" => true,
+                        _ => return Err(format!("order must be asc or desc, not '{}'", value)),
+                    }
+                }
+                _ => filters.push((field_named(key)?, value.clone())),
+            }
+        }
+
+        let any_destination = filters.iter().any(|(f, _)| *f == Field::Destination);
+        let mut missions: Vec<&Mission> = self
+            .sources
+            .iter()
+            .flat_map(|s| &s.records)
+            .filter(|(m, r)| match r {
+                None => true,
+                Some(Rejection::NotMars) => any_destination && m.is_successful(self.config),
+                Some(_) => false,
+            })
+            .map(|(m, _)| m)
+            .filter(|m| filters.iter().all(|(f, v)| f.text(m).eq_ignore_ascii_case(v)))
+            .collect();
+
+        let numeric = matches!(sort, Field::CrewSize | Field::Duration | Field::SuccessRate);
+        missions.sort_by(|a, b| {
+            let order = if numeric {
+                sort.number(a).unwrap_or(0.0).total_cmp(&sort.number(b).unwrap_or(0.0))
+            } else {
+                sort.text(a).cmp(&sort.text(b))
+            };
+            if descending { order.reverse() } else { order }
+        });
+        if let Some(top) = top {
+            missions.truncate(top);
+        }
+
+        Ok(JsonOutput {
+            statistics: self.statistics(),
+            missions: missions.iter().enumerate().map(|(idx, m)| m.to_output(idx + 1)).collect(),
+        })
+    }
+
+    /// `/missions/{id}`: every record of one mission ID, like `show`.
+    fn mission(&self, id: &str) -> Reply {
+        let found: Vec<Found> = self
+            .sources
+            .iter()
+            .flat_map(|source| {
+                let file = source.path.display().to_string();
+                source
+                    .records
+                    .iter()
+                    .filter(|(m, _)| m.mission_id.eq_ignore_ascii_case(id))
+                    .map(move |(m, r)| Found { file: file.clone(), mission: m.clone(), rejection: r.map(Rejection::as_str) })
+            })
+            .collect();
+
+        if found.is_empty() {
+            Reply::error(404, format!("no mission with ID {}", id))
+        } else {
+            Reply::ok(&found)
+        }
+    }
+}
+
+fn field_named(name: &str) -> Result<Field, String> {
+    Field::ALL
+        .iter()
+        .copied()
+        .find(|f| f.as_str() == name)
+        .ok_or_else(|| format!("unknown field '{}'", name))
+}
+
+/// Binds to `port` on the loopback interface only; port 0 picks a free one.
+pub fn bind(port: u16) -> Result<Server, String> {
+    Server::http(("127.0.0.1", port)).map_err(|e| format!("Failed to listen on 127.0.0.1:{}: {}", port, e))
+}
+
+/// Answers requests until the process is stopped.
+pub fn run(server: &Server, catalog: &mut Catalog) {
+    let content_type = Header::from_bytes("Content-Type", "application/json").expect("static header is valid");
+
+    for request in server.incoming_requests() {
+        catalog.reload_changed();
+        let method = match request.method() {
+            Method::Get => "GET",
+            _ => "OTHER",
+        };
+        let reply = catalog.handle(method, request.url());
+        let response = Response::from_string(reply.body.to_string())
+            .with_status_code(reply.status)
+            .with_header(content_type.clone());
+        if let Err(e) = request.respond(response) {
+            eprintln!("Warning: Failed to send response: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    fn write_log(dir: &tempfile::TempDir, name: &str, lines: &[&str]) -> PathBuf {
+        let path = dir.path().join(name);
+        fs::write(&path, lines.join("\n") + "\n").unwrap();
+        path
+    }
+
+    fn logs(dir: &tempfile::TempDir) -> Vec<PathBuf> {
+        vec![
+            write_log(dir, "a.log", &[
+                "2045-01-01 | AAA-0001 | Mars | Completed | 3 | 300 | 99.0 | ABC-123-XYZ",
+                "2045-01-02 | BBB-0002 | Mars | Failed | 3 | 900 | 80.0 | ABC-123-XYZ",
+            ]),
+            write_log(dir, "b.log", &[
+                "2045-02-01 | CCC-0003 | Mars | Completed | 4 | 500 | 90.0 | ABC-123-XYZ",
+                "2045-02-02 | DDD-0004 | Jupiter | Completed | 4 | 700 | 95.0 | ABC-123-XYZ",
+            ]),
+        ]
+    }
+
+    fn ids(reply: &Reply) -> Vec<&str> {
+        reply.body["missions"].as_array().unwrap().iter().map(|m| m["mission_id"].as_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_missions_endpoint() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = Config::default();
+        let catalog = Catalog::load(&logs(&dir), &config, false).unwrap();
+
+        let reply = catalog.handle("GET", "/missions");
+        assert_eq!(reply.status, 200);
+        assert_eq!(ids(&reply), vec!["CCC-0003", "AAA-0001"]);
+        assert_eq!(reply.body["missions"][0]["rank"], 1);
+        assert_eq!(reply.body["statistics"]["total_lines"], 4);
+
+        let reply = catalog.handle("GET", "/missions?sort=success_rate&top=1");
+        assert_eq!(ids(&reply), vec!["AAA-0001"]);
+
+        let reply = catalog.handle("GET", "/missions?destination=jupiter&status=completed");
+        assert_eq!(ids(&reply), vec!["DDD-0004"]);
+        assert_eq!(reply.body["missions"][0]["destination"], "Jupiter");
+        assert_eq!(reply.body["missions"][0]["status"], "Completed");
+
+        assert_eq!(catalog.handle("GET", "/missions?top=lots").status, 400);
+        assert_eq!(catalog.handle("GET", "/missions?weight=3").status, 400);
+    }
+
+    #[test]
+    fn test_other_endpoints() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = Config::default();
+        let catalog = Catalog::load(&logs(&dir), &config, false).unwrap();
+
+        let reply = catalog.handle("GET", "/missions/bbb-0002");
+        assert_eq!(reply.status, 200);
+        assert_eq!(reply.body[0]["rejection"], "not_completed");
+        assert!(reply.body[0]["file"].as_str().unwrap().ends_with("a.log"));
+
+        assert_eq!(catalog.handle("GET", "/missions/ZZZ-0000").status, 404);
+        assert_eq!(catalog.handle("GET", "/stats").body["valid_missions"], 2);
+        assert_eq!(catalog.handle("GET", "/nowhere").status, 404);
+        assert_eq!(catalog.handle("POST", "/stats").status, 405);
+    }
+
+    #[test]
+    fn test_reload_on_change() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = Config::default();
+        let paths = logs(&dir);
+        let mut catalog = Catalog::load(&paths, &config, false).unwrap();
+
+        let mut file = fs::OpenOptions::new().append(true).open(&paths[0]).unwrap();
+        writeln!(file, "2045-03-01 | EEE-0005 | Mars | Completed | 3 | 999 | 90.0 | ABC-123-XYZ").unwrap();
+        catalog.reload_changed();
+
+        assert_eq!(ids(&catalog.handle("GET", "/missions?top=1")), vec!["EEE-0005"]);
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("In%20Progress"), "In Progress");
+        assert_eq!(percent_decode("a+b"), "a b");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(query_pairs("status=In+Progress&top=2"), vec![
+            ("status".to_string(), "In Progress".to_string()),
+            ("top".to_string(), "2".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_server_over_loopback() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let paths = logs(&dir);
+        let server = bind(0).unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+
+        std::thread::spawn(move || {
+            let config = Config::default();
+            let mut catalog = Catalog::load(&paths, &config, false).unwrap();
+            run(&server, &mut catalog);
+        });
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET /missions?top=1 HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.contains("Content-Type: application/json"));
+        assert!(response.contains("\"mission_id\":\"CCC-0003\""));
+    }
+}
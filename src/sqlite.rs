@@ -0,0 +1,175 @@
+use crate::{Mission, Rejection, Statistics};
+use rusqlite::{params, Connection};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    source_file TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    total_lines INTEGER NOT NULL,
+    data_lines INTEGER NOT NULL,
+    mars_missions INTEGER NOT NULL,
+    completed_mars_missions INTEGER NOT NULL,
+    valid_missions INTEGER NOT NULL,
+    errors INTEGER NOT NULL,
+    read_errors INTEGER NOT NULL,
+    invalid_format INTEGER NOT NULL,
+    zero_duration INTEGER NOT NULL,
+    invalid_security_code INTEGER NOT NULL,
+    invalid_mission_id INTEGER NOT NULL,
+    crew_size_out_of_range INTEGER NOT NULL,
+    duration_out_of_range INTEGER NOT NULL,
+    success_rate_out_of_range INTEGER NOT NULL,
+    rule_violations INTEGER NOT NULL,
+    warnings INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS missions (
+    id INTEGER PRIMARY KEY,
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    line_number INTEGER NOT NULL,
+    date TEXT NOT NULL,
+    mission_id TEXT NOT NULL,
+    destination TEXT NOT NULL,
+    status TEXT NOT NULL,
+    crew_size INTEGER NOT NULL,
+    duration INTEGER NOT NULL,
+    success_rate REAL NOT NULL,
+    security_code TEXT NOT NULL,
+    valid INTEGER NOT NULL,
+    rejection TEXT
+);
+
+CREATE INDEX IF NOT EXISTS missions_run_id ON missions(run_id);
+CREATE INDEX IF NOT EXISTS missions_mission_id ON missions(mission_id);
+";
+
+/// Appends one run to the database at `db_path`, creating the schema if
+/// needed. Returns the new run id.
+pub fn export(
+    db_path: &Path,
+    source: &str,
+    records: &[(Mission, Option<Rejection>)],
+    stats: &Statistics,
+) -> Result<i64, String> {
+    let mut conn = Connection::open(db_path)
+        .map_err(|e| format!("Failed to open SQLite database: {}", e))?;
+    write_run(&mut conn, source, records, stats)
+        .map_err(|e| format!("Failed to export to SQLite: {}", e))
+}
+
+fn write_run(
+    conn: &mut Connection,
+    source: &str,
+    records: &[(Mission, Option<Rejection>)],
+    stats: &Statistics,
+) -> rusqlite::Result<i64> {
+    conn.execute_batch(SCHEMA)?;
+
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+
+    let tx = conn.transaction()?;
+
+    tx.execute(
+        "INSERT INTO runs (source_file, created_at, total_lines, data_lines, mars_missions,
+             completed_mars_missions, valid_missions, errors, read_errors, invalid_format,
+             zero_duration, invalid_security_code, invalid_mission_id, crew_size_out_of_range,
+             duration_out_of_range, success_rate_out_of_range, rule_violations, warnings)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
+        params![
+            source,
+            created_at,
+            stats.total_lines as i64,
+            stats.data_lines as i64,
+            stats.mars_missions as i64,
+            stats.completed_mars_missions as i64,
+            stats.valid_missions as i64,
+            stats.errors as i64,
+            stats.rejections.read_errors as i64,
+            stats.rejections.invalid_format as i64,
+            stats.rejections.zero_duration as i64,
+            stats.rejections.invalid_security_code as i64,
+            stats.rejections.invalid_mission_id as i64,
+            stats.rejections.crew_size_out_of_range as i64,
+            stats.rejections.duration_out_of_range as i64,
+            stats.rejections.success_rate_out_of_range as i64,
+            stats.rejections.rule_violations as i64,
+            stats.warnings as i64,
+        ],
+    )?;
+    let run_id = tx.last_insert_rowid();
+
+    {
+        let mut insert = tx.prepare(
+            "INSERT INTO missions (run_id, line_number, date, mission_id, destination, status,
+                 crew_size, duration, success_rate, security_code, valid, rejection)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        )?;
+
+        for (mission, rejection) in records {
+            insert.execute(params![
+                run_id,
+                mission.line_number as i64,
+                mission.date,
+                mission.mission_id,
+                mission.destination,
+                mission.status,
+                mission.crew_size,
+                mission.duration,
+                mission.success_rate,
+                mission.security_code,
+                rejection.is_none(),
+                rejection.map(Rejection::as_str),
+            ])?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(run_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_run() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let records = vec![
+            (
+                Mission::from_line("2045-07-12 | M1 | Mars | Completed | 5 | 500 | 98.7 | TRX-842-YHG", 1).unwrap(),
+                None,
+            ),
+            (
+                Mission::from_line("2045-07-13 | M2 | Venus | Completed | 3 | 200 | 90.0 | ABC-123-XYZ", 2).unwrap(),
+                Some(Rejection::NotMars),
+            ),
+        ];
+        let mut stats = Statistics { total_lines: 2, data_lines: 2, valid_missions: 1, ..Default::default() };
+        stats.rejections.crew_size_out_of_range = 2;
+
+        let first = write_run(&mut conn, "a.log", &records, &stats).unwrap();
+        let second = write_run(&mut conn, "a.log", &records, &stats).unwrap();
+        assert_ne!(first, second);
+
+        let valid: i64 = conn
+            .query_row("SELECT COUNT(*) FROM missions WHERE run_id = ?1 AND valid = 1", [first], |r| r.get(0))
+            .unwrap();
+        assert_eq!(valid, 1);
+
+        let rejection: String = conn
+            .query_row("SELECT rejection FROM missions WHERE mission_id = 'M2' AND run_id = ?1", [second], |r| r.get(0))
+            .unwrap();
+        assert_eq!(rejection, "not_mars");
+
+        let crew: i64 = conn
+            .query_row("SELECT crew_size_out_of_range FROM runs WHERE id = ?1", [first], |r| r.get(0))
+            .unwrap();
+        assert_eq!(crew, 2);
+    }
+}
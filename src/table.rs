@@ -0,0 +1,207 @@
+use std::io::IsTerminal;
+
+const BOLD: &str = "\x1b[1m";
+const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+/// Columns are never shrunk below this width when fitting the terminal.
+const MIN_COLUMN_WIDTH: usize = 4;
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                std::env::var_os("NO_COLOR").is_none() && std::io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+/// Width available for table output: `COLUMNS` wins, then the attached
+/// terminal. Returns `None` when writing to a pipe or file.
+pub fn terminal_width() -> Option<usize> {
+    if let Some(cols) = std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok()) {
+        return Some(cols);
+    }
+
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+
+    terminal_size::terminal_size().map(|(w, _)| w.0 as usize)
+}
+
+fn looks_numeric(cell: &str) -> bool {
+    !cell.is_empty() && cell.parse::<f64>().is_ok()
+}
+
+fn truncate(cell: &str, width: usize) -> String {
+    if cell.chars().count() <= width {
+        return cell.to_string();
+    }
+    let mut out: String = cell.chars().take(width.saturating_sub(1)).collect();
+    out.push('…');
+    out
+}
+
+/// Shrinks the widest columns one character at a time until the rendered
+/// table (including two-space gutters) fits in `max_width`.
+fn fit_widths(widths: &mut [usize], max_width: usize) {
+    let gutter = 2 * widths.len().saturating_sub(1);
+
+    while widths.iter().sum::<usize>() + gutter > max_width {
+        let (idx, &widest) = match widths.iter().enumerate().max_by_key(|(_, w)| **w) {
+            Some(entry) => entry,
+            None => return,
+        };
+        if widest <= MIN_COLUMN_WIDTH {
+            return;
+        }
+        widths[idx] -= 1;
+    }
+}
+
+/// Renders rows as aligned columns. Numeric columns are right-aligned.
+pub fn render_table(
+    headers: &[&str],
+    rows: &[Vec<String>],
+    max_width: Option<usize>,
+    color: bool,
+) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (idx, cell) in row.iter().enumerate() {
+            widths[idx] = widths[idx].max(cell.chars().count());
+        }
+    }
+
+    if let Some(max_width) = max_width {
+        fit_widths(&mut widths, max_width);
+    }
+
+    let numeric: Vec<bool> = (0..headers.len())
+        .map(|idx| !rows.is_empty() && rows.iter().all(|r| looks_numeric(&r[idx])))
+        .collect();
+
+    let format_row = |cells: Vec<String>| -> String {
+        cells
+            .iter()
+            .enumerate()
+            .map(|(idx, cell)| {
+                let cell = truncate(cell, widths[idx]);
+                if numeric[idx] {
+                    format!("{:>width$}", cell, width = widths[idx])
+                } else {
+                    format!("{:<width$}", cell, width = widths[idx])
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut out = String::new();
+
+    let header = format_row(headers.iter().map(|h| h.to_string()).collect());
+    let rule = widths.iter().map(|w| "─".repeat(*w)).collect::<Vec<_>>().join("  ");
+    if color {
+        out.push_str(&format!("{}{}{}\n", BOLD, header, RESET));
+        out.push_str(&format!("{}{}{}\n", CYAN, rule, RESET));
+    } else {
+        out.push_str(&format!("{}\n{}\n", header, rule));
+    }
+
+    for row in rows {
+        out.push_str(&format_row(row.clone()));
+        out.push('\n');
+    }
+
+    out
+}
+
+fn escape_markdown(cell: &str) -> String {
+    cell.replace('|', "\\|")
+}
+
+/// Renders rows as a GitHub-flavored Markdown table.
+pub fn render_markdown(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let numeric: Vec<bool> = (0..headers.len())
+        .map(|idx| !rows.is_empty() && rows.iter().all(|r| looks_numeric(&r[idx])))
+        .collect();
+
+    let mut out = String::new();
+
+    out.push_str(&format!("| {} |\n", headers.join(" | ")));
+
+    let separators: Vec<&str> = numeric
+        .iter()
+        .map(|&n| if n { "---:" } else { "---" })
+        .collect();
+    out.push_str(&format!("| {} |\n", separators.join(" | ")));
+
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|c| escape_markdown(c)).collect();
+        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> (Vec<&'static str>, Vec<Vec<String>>) {
+        let headers = vec!["Mission ID", "Duration"];
+        let rows = vec![
+            vec!["KLM-1234".to_string(), "900".to_string()],
+            vec!["AB".to_string(), "50".to_string()],
+        ];
+        (headers, rows)
+    }
+
+    #[test]
+    fn test_render_table_aligns_columns() {
+        let (headers, rows) = sample();
+        let out = render_table(&headers, &rows, None, false);
+        let lines: Vec<&str> = out.lines().collect();
+
+        assert_eq!(lines[0], "Mission ID  Duration");
+        assert_eq!(lines[2], "KLM-1234         900");
+        assert_eq!(lines[3], "AB                50");
+        assert!(!out.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_render_table_fits_width() {
+        let (headers, rows) = sample();
+        let out = render_table(&headers, &rows, Some(14), false);
+
+        for line in out.lines() {
+            assert!(line.chars().count() <= 14, "line too wide: {:?}", line);
+        }
+        assert!(out.contains('…'));
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let (headers, mut rows) = sample();
+        rows[1][0] = "A|B".to_string();
+        let out = render_markdown(&headers, &rows);
+
+        assert_eq!(
+            out,
+            "| Mission ID | Duration |\n| --- | ---: |\n| KLM-1234 | 900 |\n| A\\|B | 50 |\n"
+        );
+    }
+}
@@ -0,0 +1,169 @@
+//! Shared rendering for analysis modes whose results are a flat table of
+//! typed rows (`stats`, and friends). Missions keep their dedicated printers
+//! in `main.rs`; everything else goes through [`print_tabular`].
+
+use crate::table::{self, ColorChoice};
+use crate::{columnar, report, OutputFormat};
+use serde::Serialize;
+use std::io::{self, IsTerminal, Write};
+use std::process;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Int(i64),
+    Float(f64),
+    /// A missing value; blank in text formats and null in columnar ones,
+    /// whatever the column's type.
+    Null,
+}
+
+impl Cell {
+    pub fn text(value: impl Into<String>) -> Self {
+        Cell::Text(value.into())
+    }
+
+    /// Human-facing rendering; floats are rounded to two decimals.
+    pub fn display(&self) -> String {
+        match self {
+            Cell::Text(s) => s.clone(),
+            Cell::Int(i) => i.to_string(),
+            Cell::Float(f) => format!("{:.2}", f),
+            Cell::Null => String::new(),
+        }
+    }
+
+    /// Machine-facing rendering with full float precision.
+    fn raw(&self) -> String {
+        match self {
+            Cell::Float(f) => f.to_string(),
+            other => other.display(),
+        }
+    }
+}
+
+/// A titled table plus the structured documents used for JSON and NDJSON.
+pub struct Tabular<'a, J: Serialize, N: Serialize> {
+    pub title: &'a str,
+    pub headers: &'a [&'a str],
+    pub rows: &'a [Vec<Cell>],
+    pub json: &'a J,
+    pub ndjson: &'a [N],
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub fn render_csv(headers: &[&str], rows: &[Vec<Cell>]) -> String {
+    let mut out = String::new();
+    let header: Vec<String> = headers.iter().map(|h| csv_escape(h)).collect();
+    out.push_str(&header.join(","));
+    out.push('\n');
+
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|c| csv_escape(&c.raw())).collect();
+        out.push_str(&cells.join(","));
+        out.push('\n');
+    }
+
+    out
+}
+
+fn display_rows(rows: &[Vec<Cell>]) -> Vec<Vec<String>> {
+    rows.iter()
+        .map(|row| row.iter().map(Cell::display).collect())
+        .collect()
+}
+
+/// Writes a binary columnar format to stdout, refusing to dump it on a terminal.
+pub fn write_binary<F>(write: F)
+where
+    F: FnOnce(io::Stdout) -> Result<(), String>,
+{
+    let stdout = io::stdout();
+    if stdout.is_terminal() {
+        eprintln!("ERROR: Refusing to write binary output to a terminal. Redirect stdout to a file.");
+        process::exit(1);
+    }
+
+    if let Err(e) = write(stdout) {
+        eprintln!("ERROR: {}", e);
+        process::exit(1);
+    }
+}
+
+/// Prints `data` in the requested format. `OutputFormat::Default` renders
+/// the same aligned table as `--format table`.
+pub fn print_tabular<J: Serialize, N: Serialize>(
+    data: &Tabular<J, N>,
+    format: OutputFormat,
+    color: ColorChoice,
+) {
+    match format {
+        OutputFormat::Default | OutputFormat::Table => print!(
+            "{}",
+            table::render_table(
+                data.headers,
+                &display_rows(data.rows),
+                table::terminal_width(),
+                color.enabled()
+            )
+        ),
+        OutputFormat::Markdown => print!(
+            "{}",
+            table::render_markdown(data.headers, &display_rows(data.rows))
+        ),
+        OutputFormat::Json => match serde_json::to_string_pretty(data.json) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("Error serializing to JSON: {}", e),
+        },
+        OutputFormat::Ndjson => {
+            let stdout = io::stdout();
+            let mut out = stdout.lock();
+            for item in data.ndjson {
+                let result = serde_json::to_writer(&mut out, item)
+                    .map_err(io::Error::from)
+                    .and_then(|_| writeln!(out));
+                if let Err(e) = result {
+                    eprintln!("Error writing NDJSON output: {}", e);
+                    return;
+                }
+            }
+        }
+        OutputFormat::Csv => print!("{}", render_csv(data.headers, data.rows)),
+        OutputFormat::Html => print!(
+            "{}",
+            report::render_html_table(data.title, data.headers, &display_rows(data.rows))
+        ),
+        OutputFormat::Parquet => {
+            write_binary(|out| columnar::write_rows_parquet(out, data.headers, data.rows))
+        }
+        OutputFormat::Arrow => {
+            write_binary(|out| columnar::write_rows_arrow(out, data.headers, data.rows))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_csv_escapes() {
+        let rows = vec![vec![Cell::text("Mars, Inc"), Cell::Int(3), Cell::Float(1.5)]];
+        let out = render_csv(&["Group", "Count", "Mean"], &rows);
+
+        assert_eq!(out, "Group,Count,Mean\n\"Mars, Inc\",3,1.5\n");
+    }
+
+    #[test]
+    fn test_cell_display() {
+        assert_eq!(Cell::Float(2.0 / 3.0).display(), "0.67");
+        assert_eq!(Cell::Int(-4).display(), "-4");
+    }
+}
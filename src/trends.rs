@@ -0,0 +1,159 @@
+use crate::tabular::Cell;
+use crate::Mission;
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Period {
+    Year,
+    Quarter,
+}
+
+impl Period {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Period::Year => "year",
+            Period::Quarter => "quarter",
+        }
+    }
+
+    /// Sortable `(year, quarter)` key; quarter is 0 for yearly periods.
+    fn key(self, date: NaiveDate) -> (i32, u32) {
+        match self {
+            Period::Year => (date.year(), 0),
+            Period::Quarter => (date.year(), date.month0() / 3 + 1),
+        }
+    }
+
+    fn label(self, key: (i32, u32)) -> String {
+        match self {
+            Period::Year => key.0.to_string(),
+            Period::Quarter => format!("{}-Q{}", key.0, key.1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrendPoint {
+    pub period: String,
+    pub missions: usize,
+    pub completed: usize,
+    pub completion_rate: f64,
+    pub avg_duration: f64,
+    pub avg_success_rate: f64,
+}
+
+#[derive(Default)]
+struct Accumulator {
+    missions: usize,
+    completed: usize,
+    duration_sum: f64,
+    success_sum: f64,
+}
+
+/// Buckets missions into chronological periods. Missions whose date is not
+/// a valid `YYYY-MM-DD` are skipped and counted in the second return value.
+pub fn series(missions: &[Mission], period: Period) -> (Vec<TrendPoint>, usize) {
+    let mut periods: BTreeMap<(i32, u32), Accumulator> = BTreeMap::new();
+    let mut undated = 0;
+
+    for mission in missions {
+        let date = match NaiveDate::parse_from_str(&mission.date, "%Y-%m-%d") {
+            Ok(d) => d,
+            Err(_) => {
+                undated += 1;
+                continue;
+            }
+        };
+
+        let acc = periods.entry(period.key(date)).or_default();
+        acc.missions += 1;
+        if mission.status.eq_ignore_ascii_case("completed") {
+            acc.completed += 1;
+        }
+        acc.duration_sum += mission.duration as f64;
+        acc.success_sum += mission.success_rate;
+    }
+
+    let points = periods
+        .into_iter()
+        .map(|(key, acc)| {
+            let n = acc.missions as f64;
+            TrendPoint {
+                period: period.label(key),
+                missions: acc.missions,
+                completed: acc.completed,
+                completion_rate: 100.0 * acc.completed as f64 / n,
+                avg_duration: acc.duration_sum / n,
+                avg_success_rate: acc.success_sum / n,
+            }
+        })
+        .collect();
+
+    (points, undated)
+}
+
+pub const HEADERS: [&str; 6] = [
+    "Period",
+    "Missions",
+    "Completed",
+    "Completion Rate (%)",
+    "Avg Duration (days)",
+    "Avg Success Rate",
+];
+
+pub fn rows(points: &[TrendPoint]) -> Vec<Vec<Cell>> {
+    points
+        .iter()
+        .map(|p| {
+            vec![
+                Cell::text(p.period.as_str()),
+                Cell::Int(p.missions as i64),
+                Cell::Int(p.completed as i64),
+                Cell::Float(p.completion_rate),
+                Cell::Float(p.avg_duration),
+                Cell::Float(p.avg_success_rate),
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn missions() -> Vec<Mission> {
+        [
+            "2046-02-01 | A | Mars | Completed | 3 | 100 | 90.0 | ABC-123-XYZ",
+            "2045-11-30 | B | Mars | Failed | 3 | 300 | 50.0 | ABC-123-XYZ",
+            "2045-01-15 | C | Moon | completed | 3 | 200 | 80.0 | ABC-123-XYZ",
+            "someday | D | Mars | Completed | 3 | 100 | 90.0 | ABC-123-XYZ",
+        ]
+        .iter()
+        .map(|l| Mission::from_line(l, 1).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn test_yearly_series() {
+        let (points, undated) = series(&missions(), Period::Year);
+
+        assert_eq!(undated, 1);
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].period, "2045");
+        assert_eq!(points[0].missions, 2);
+        assert_eq!(points[0].completed, 1);
+        assert_eq!(points[0].completion_rate, 50.0);
+        assert_eq!(points[0].avg_duration, 250.0);
+        assert_eq!(points[1].period, "2046");
+    }
+
+    #[test]
+    fn test_quarterly_series() {
+        let (points, _) = series(&missions(), Period::Quarter);
+        let periods: Vec<&str> = points.iter().map(|p| p.period.as_str()).collect();
+
+        assert_eq!(periods, vec!["2045-Q1", "2045-Q4", "2046-Q1"]);
+    }
+}
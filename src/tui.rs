@@ -0,0 +1,374 @@
+use crate::follow::{Batch, Follower, POLL_INTERVAL};
+use crate::rules::Field;
+use crate::validation::Config;
+use crate::{mission_details, scan_line, statistics_lines, Mission, Rejection, Statistics};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Row, Table, TableState};
+use ratatui::Frame;
+use std::path::Path;
+use std::time::Duration;
+
+const HELP: &str = "q quit  ↑↓ move  1-8/s sort  r reverse  / filter  a all records";
+
+/// What the dashboard shows and how; everything but the terminal itself.
+pub struct App {
+    source: String,
+    records: Vec<(Mission, Option<Rejection>)>,
+    stats: Statistics,
+    sort: Field,
+    descending: bool,
+    /// Case-insensitive text that some field of a shown record must contain.
+    filter: String,
+    editing_filter: bool,
+    /// Show rejected records as well as the ones the ranking would use.
+    all_records: bool,
+    /// Indices into `records`, filtered and sorted.
+    view: Vec<usize>,
+    selected: usize,
+}
+
+impl App {
+    pub fn new(source: &str) -> Self {
+        App {
+            source: source.to_string(),
+            records: Vec::new(),
+            stats: Statistics::default(),
+            sort: Field::Duration,
+            descending: true,
+            filter: String::new(),
+            editing_filter: false,
+            all_records: false,
+            view: Vec::new(),
+            selected: 0,
+        }
+    }
+
+    /// Adds newly read lines, starting over if the log was truncated.
+    pub fn ingest(&mut self, batch: Batch, config: &Config) {
+        if batch.truncated {
+            self.records.clear();
+            self.stats = Statistics::default();
+            self.view.clear();
+            self.selected = 0;
+        }
+        if batch.lines.is_empty() && !batch.truncated {
+            return;
+        }
+
+        let records = &mut self.records;
+        for (line_number, line) in &batch.lines {
+            self.stats.total_lines += 1;
+            scan_line(line, *line_number, config, false, &mut self.stats, &mut |m, r| records.push((m, r)));
+        }
+        self.refresh();
+    }
+
+    fn selected_record(&self) -> Option<&(Mission, Option<Rejection>)> {
+        self.view.get(self.selected).map(|&idx| &self.records[idx])
+    }
+
+    /// Rebuilds the view, keeping the same record selected when it is still
+    /// shown. Records are tracked by position, since line numbers start over
+    /// when the log is rotated.
+    fn refresh(&mut self) {
+        let current = self.view.get(self.selected).copied();
+        let needle = self.filter.to_ascii_lowercase();
+
+        self.view = (0..self.records.len())
+            .filter(|&idx| {
+                let (mission, rejection) = &self.records[idx];
+                (self.all_records || rejection.is_none())
+                    && (needle.is_empty()
+                        || Field::ALL.iter().any(|f| f.text(mission).to_ascii_lowercase().contains(&needle)))
+            })
+            .collect();
+
+        let (sort, records) = (self.sort, &self.records);
+        self.view.sort_by(|&ia, &ib| {
+            let (a, b) = (&records[ia].0, &records[ib].0);
+            let order = match (sort.number(a), sort.number(b)) {
+                (Some(x), Some(y)) if matches!(sort, Field::CrewSize | Field::Duration | Field::SuccessRate) => {
+                    x.total_cmp(&y)
+                }
+                _ => sort.text(a).to_ascii_lowercase().cmp(&sort.text(b).to_ascii_lowercase()),
+            };
+            let order = if self.descending { order.reverse() } else { order };
+            order.then(ia.cmp(&ib))
+        });
+
+        self.selected = current
+            .and_then(|current| self.view.iter().position(|&idx| idx == current))
+            .unwrap_or(0);
+    }
+
+    fn sort_by(&mut self, field: Field) {
+        if self.sort == field {
+            self.descending = !self.descending;
+        } else {
+            self.sort = field;
+            self.descending = matches!(field, Field::CrewSize | Field::Duration | Field::SuccessRate);
+        }
+        self.refresh();
+    }
+
+    /// Applies one key press; returns true when the user asked to quit.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if self.editing_filter {
+            match key.code {
+                KeyCode::Enter | KeyCode::Esc => self.editing_filter = false,
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                    self.refresh();
+                }
+                KeyCode::Char(c) => {
+                    self.filter.push(c);
+                    self.refresh();
+                }
+                _ => {}
+            }
+            return false;
+        }
+
+        let last = self.view.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            KeyCode::Down | KeyCode::Char('j') => self.selected = (self.selected + 1).min(last),
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::PageDown => self.selected = (self.selected + 10).min(last),
+            KeyCode::PageUp => self.selected = self.selected.saturating_sub(10),
+            KeyCode::Home | KeyCode::Char('g') => self.selected = 0,
+            KeyCode::End | KeyCode::Char('G') => self.selected = last,
+            KeyCode::Char(c @ '1'..='8') => self.sort_by(Field::ALL[c as usize - '1' as usize]),
+            KeyCode::Char('s') => {
+                let next = Field::ALL.iter().position(|&f| f == self.sort).map_or(0, |i| (i + 1) % Field::ALL.len());
+                self.sort_by(Field::ALL[next]);
+            }
+            KeyCode::Char('r') => {
+                self.descending = !self.descending;
+                self.refresh();
+            }
+            KeyCode::Char('a') => {
+                self.all_records = !self.all_records;
+                self.refresh();
+            }
+            KeyCode::Char('/') => self.editing_filter = true,
+            _ => {}
+        }
+        false
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let [main, detail, footer] =
+            Layout::vertical([Constraint::Min(5), Constraint::Length(10), Constraint::Length(1)]).areas(frame.area());
+        let [table, side] = Layout::horizontal([Constraint::Min(40), Constraint::Length(34)]).areas(main);
+
+        self.draw_table(frame, table);
+
+        let mut side_lines: Vec<Line> = statistics_lines(&self.stats).into_iter().map(Line::from).collect();
+        side_lines.push(Line::from(""));
+        side_lines.push(Line::from(if self.all_records { "Showing: all records" } else { "Showing: rankable only" }));
+        frame.render_widget(Paragraph::new(side_lines).block(Block::bordered().title(" Statistics ")), side);
+
+        let detail_lines: Vec<Line> = match self.selected_record() {
+            Some((mission, rejection)) => {
+                let mut lines = mission_details(mission);
+                lines.push(match rejection {
+                    None => "Ranking: eligible".to_string(),
+                    Some(r) => format!("Ranking: excluded ({})", r.as_str()),
+                });
+                lines.into_iter().map(Line::from).collect()
+            }
+            None => vec![Line::from("No missions match.")],
+        };
+        frame.render_widget(Paragraph::new(detail_lines).block(Block::bordered().title(" Mission ")), detail);
+
+        let footer_text = if self.editing_filter || !self.filter.is_empty() {
+            format!("/{}{}   {}", self.filter, if self.editing_filter { "_" } else { "" }, HELP)
+        } else {
+            HELP.to_string()
+        };
+        frame.render_widget(Paragraph::new(footer_text), footer);
+    }
+
+    fn draw_table(&self, frame: &mut Frame, area: Rect) {
+        let header = Row::new(Field::ALL.iter().enumerate().map(|(idx, f)| {
+            let marker = match (f == &self.sort, self.descending) {
+                (true, true) => " ↓",
+                (true, false) => " ↑",
+                _ => "",
+            };
+            format!("{} {}{}", idx + 1, f.as_str(), marker)
+        }))
+        .style(Style::new().add_modifier(Modifier::BOLD));
+
+        let rows = self.view.iter().map(|&idx| Row::new(Field::ALL.iter().map(|f| f.text(&self.records[idx].0))));
+        let widths = [
+            Constraint::Length(12),
+            Constraint::Length(14),
+            Constraint::Length(15),
+            Constraint::Length(14),
+            Constraint::Length(13),
+            Constraint::Length(12),
+            Constraint::Length(16),
+            Constraint::Length(17),
+        ];
+        let title = format!(" {}: {} of {} records ", self.source, self.view.len(), self.records.len());
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(Block::bordered().title(title))
+            .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+
+        let mut state = TableState::default().with_selected((!self.view.is_empty()).then_some(self.selected));
+        frame.render_stateful_widget(table, area, &mut state);
+    }
+}
+
+/// Runs the dashboard until the user quits, following `file_path` as it
+/// grows, is truncated, or is rotated.
+pub fn run(file_path: &Path, config: &Config) -> Result<(), String> {
+    let mut follower = Follower::new(file_path);
+    let mut app = App::new(&file_path.display().to_string());
+    let read_error = |e: std::io::Error| format!("Failed to read {}: {}", file_path.display(), e);
+
+    // Load before taking over the screen, so a bad path is reported normally;
+    // the follower itself would wait for a missing file to appear
+    std::fs::metadata(file_path).map_err(read_error)?;
+    let batch = follower.poll().map_err(read_error)?;
+    let mut more = batch.more;
+    app.ingest(batch, config);
+
+    let mut terminal = ratatui::init();
+    let result = (|| loop {
+        terminal.draw(|frame| app.draw(frame)).map_err(|e| e.to_string())?;
+
+        // Keep reading without waiting while a large file is still loading
+        let wait = if more { Duration::ZERO } else { POLL_INTERVAL };
+        if event::poll(wait).map_err(|e| e.to_string())? {
+            if let Event::Key(key) = event::read().map_err(|e| e.to_string())? {
+                if key.kind == KeyEventKind::Press && app.handle_key(key) {
+                    return Ok(());
+                }
+            }
+        }
+        let batch = follower.poll().map_err(read_error)?;
+        more = batch.more;
+        app.ingest(batch, config);
+    })();
+    ratatui::restore();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::KeyModifiers;
+    use ratatui::Terminal;
+
+    fn batch(lines: &[&str]) -> Batch {
+        Batch {
+            lines: lines.iter().enumerate().map(|(idx, l)| (idx + 1, l.to_string())).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn app() -> App {
+        let mut app = App::new("missions.log");
+        app.ingest(
+            batch(&[
+                "2045-01-01 | AAA-0001 | Mars | Completed | 3 | 300 | 99.0 | ABC-123-XYZ",
+                "2045-01-02 | BBB-0002 | Mars | Completed | 5 | 500 | 80.0 | ABC-123-XYZ",
+                "2045-01-03 | CCC-0003 | Venus | Completed | 4 | 900 | 90.0 | ABC-123-XYZ",
+            ]),
+            &Config::default(),
+        );
+        app
+    }
+
+    fn press(app: &mut App, code: KeyCode) -> bool {
+        app.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn shown(app: &App) -> Vec<&str> {
+        app.view.iter().map(|&idx| app.records[idx].0.mission_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_sorting_and_toggles() {
+        let mut app = app();
+        assert_eq!(shown(&app), vec!["BBB-0002", "AAA-0001"]);
+
+        press(&mut app, KeyCode::Char('7'));
+        assert_eq!(shown(&app), vec!["AAA-0001", "BBB-0002"]);
+        press(&mut app, KeyCode::Char('r'));
+        assert_eq!(shown(&app), vec!["BBB-0002", "AAA-0001"]);
+
+        press(&mut app, KeyCode::Char('a'));
+        assert_eq!(shown(&app).len(), 3);
+    }
+
+    #[test]
+    fn test_filter_keeps_selection() {
+        let mut app = app();
+        press(&mut app, KeyCode::Char('a'));
+        assert_eq!(shown(&app), vec!["CCC-0003", "BBB-0002", "AAA-0001"]);
+        assert_eq!(app.selected_record().unwrap().0.mission_id, "BBB-0002");
+
+        for c in "b-0".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        // Typing went to the movement keys, not the filter
+        assert!(app.filter.is_empty());
+
+        press(&mut app, KeyCode::Char('/'));
+        for c in "bbb".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(shown(&app), vec!["BBB-0002"]);
+        assert_eq!(app.selected_record().unwrap().0.mission_id, "BBB-0002");
+        assert!(press(&mut app, KeyCode::Char('q')));
+    }
+
+    #[test]
+    fn test_truncation_starts_over() {
+        let mut app = app();
+        app.ingest(Batch { truncated: true, ..batch(&[]) }, &Config::default());
+        assert!(app.records.is_empty());
+        assert_eq!(app.stats.total_lines, 0);
+    }
+
+    #[test]
+    fn test_rotation_keeps_selection() {
+        let mut app = app();
+        press(&mut app, KeyCode::Down);
+        assert_eq!(app.selected_record().unwrap().0.mission_id, "AAA-0001");
+
+        // The new file's line 1 sorts first and shares a line number with AAA-0001
+        let rotated = batch(&["2045-01-04 | DDD-0004 | Mars | Completed | 3 | 700 | 90.0 | ABC-123-XYZ"]);
+        app.ingest(Batch { rotated: true, ..rotated }, &Config::default());
+        assert_eq!(shown(&app), vec!["DDD-0004", "BBB-0002", "AAA-0001"]);
+        assert_eq!(app.selected_record().unwrap().0.mission_id, "AAA-0001");
+    }
+
+    #[test]
+    fn test_draw() {
+        let app = app();
+        let mut terminal = Terminal::new(TestBackend::new(140, 24)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let text: String = (0..buffer.area.height)
+            .map(|y| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect::<String>() + "\n")
+            .collect();
+
+        assert!(text.contains("missions.log: 2 of 3 records"));
+        assert!(text.contains("6 duration ↓"));
+        assert!(text.contains("Valid missions stored: 2"));
+        assert!(text.contains("Mission ID: BBB-0002"));
+        assert!(text.contains("Ranking: eligible"));
+    }
+}
@@ -0,0 +1,212 @@
+use crate::rules::RuleSet;
+use crate::{Mission, Rejection};
+use regex::Regex;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// Mission IDs look like `KLM-1234` unless the config says otherwise.
+pub const DEFAULT_MISSION_ID_PATTERN: &str = r"^[A-Z]{3}-[0-9]{4}$";
+
+/// Settings loaded from `--config`. Every section is optional. Rules come
+/// from their own file via `--rules`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub ranges: Ranges,
+    pub mission_id: MissionIdFormat,
+    #[serde(skip)]
+    pub rules: RuleSet,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+        toml::from_str(&text)
+            .map_err(|e| format!("Invalid config file {}: {}", path.display(), e))
+    }
+}
+
+fn configured() -> bool {
+    true
+}
+
+/// The `[mission_id]` section: a regex every mission ID must match. Without
+/// the section, IDs are checked against the default pattern but a mismatch
+/// is only a warning.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MissionIdFormat {
+    #[serde(with = "serde_regex")]
+    pub pattern: Regex,
+    /// Set when the section is present in the config file.
+    #[serde(skip_deserializing, default = "configured")]
+    pub enforced: bool,
+}
+
+impl Default for MissionIdFormat {
+    fn default() -> Self {
+        MissionIdFormat { pattern: Regex::new(DEFAULT_MISSION_ID_PATTERN).unwrap(), enforced: false }
+    }
+}
+
+impl MissionIdFormat {
+    pub fn is_valid(&self, mission_id: &str) -> bool {
+        self.pattern.is_match(mission_id)
+    }
+
+    /// Whether `mission_id` should keep its mission out of the ranking.
+    pub fn rejects(&self, mission_id: &str) -> bool {
+        self.enforced && !self.is_valid(mission_id)
+    }
+}
+
+mod serde_regex {
+    use regex::Regex;
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Regex, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Regex::new(&pattern).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Inclusive bounds; a missing side is unbounded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Range {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl Range {
+    pub fn contains(&self, value: f64) -> bool {
+        self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value <= max)
+    }
+}
+
+impl std::fmt::Display for Range {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => write!(f, "{}..={}", min, max),
+            (Some(min), None) => write!(f, ">= {}", min),
+            (None, Some(max)) => write!(f, "<= {}", max),
+            (None, None) => write!(f, "any value"),
+        }
+    }
+}
+
+/// Plausibility ranges for the numeric fields. A range given in the config
+/// file replaces the default for that field entirely.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Ranges {
+    pub crew_size: Range,
+    pub duration: Range,
+    pub success_rate: Range,
+}
+
+impl Default for Ranges {
+    fn default() -> Self {
+        Ranges {
+            crew_size: Range { min: Some(1.0), max: None },
+            // Zero durations already have their own check
+            duration: Range::default(),
+            success_rate: Range { min: Some(0.0), max: Some(100.0) },
+        }
+    }
+}
+
+/// A numeric field that fell outside its configured range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeViolation {
+    pub rejection: Rejection,
+    pub field: &'static str,
+    pub value: f64,
+    pub range: Range,
+}
+
+impl Ranges {
+    /// The first field of `mission` outside its range, checked in log
+    /// column order.
+    pub fn violation(&self, mission: &Mission) -> Option<RangeViolation> {
+        let checks = [
+            ("crew_size", mission.crew_size as f64, self.crew_size, Rejection::CrewSizeOutOfRange),
+            ("duration", mission.duration as f64, self.duration, Rejection::DurationOutOfRange),
+            ("success_rate", mission.success_rate, self.success_rate, Rejection::SuccessRateOutOfRange),
+        ];
+
+        checks
+            .into_iter()
+            .find(|(_, value, range, _)| !range.contains(*value))
+            .map(|(field, value, range, rejection)| RangeViolation { rejection, field, value, range })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mission(crew: u32, duration: u32, rate: f64) -> Mission {
+        let line = format!("2045-01-01 | M1 | Mars | Completed | {} | {} | {} | ABC-123-XYZ", crew, duration, rate);
+        Mission::from_line(&line, 1).unwrap()
+    }
+
+    #[test]
+    fn test_default_ranges() {
+        let ranges = Ranges::default();
+
+        assert!(ranges.violation(&mission(4, 400, 99.5)).is_none());
+        assert!(ranges.violation(&mission(1, 90000, 0.0)).is_none());
+
+        let v = ranges.violation(&mission(0, 400, 99.5)).unwrap();
+        assert_eq!(v.rejection, Rejection::CrewSizeOutOfRange);
+        assert_eq!(v.field, "crew_size");
+
+        let v = ranges.violation(&mission(4, 400, 250.0)).unwrap();
+        assert_eq!(v.rejection, Rejection::SuccessRateOutOfRange);
+        assert_eq!(v.value, 250.0);
+
+        let v = ranges.violation(&mission(4, 400, -1.0)).unwrap();
+        assert_eq!(v.rejection, Rejection::SuccessRateOutOfRange);
+    }
+
+    #[test]
+    fn test_config_overrides_only_given_fields() {
+        let config: Config = toml::from_str(
+            "[ranges]\nduration = { max = 5000 }\ncrew_size = { min = 2, max = 12 }\n",
+        )
+        .unwrap();
+
+        assert_eq!(config.ranges.duration, Range { min: None, max: Some(5000.0) });
+        assert_eq!(config.ranges.crew_size, Range { min: Some(2.0), max: Some(12.0) });
+        assert_eq!(config.ranges.success_rate, Ranges::default().success_rate);
+
+        let v = config.ranges.violation(&mission(4, 90000, 50.0)).unwrap();
+        assert_eq!(v.rejection, Rejection::DurationOutOfRange);
+        assert_eq!(v.range.to_string(), "<= 5000");
+    }
+
+    #[test]
+    fn test_mission_id_format() {
+        let default = MissionIdFormat::default();
+        assert!(default.is_valid("KLM-1234"));
+        assert!(!default.is_valid("KLM-123"));
+        assert!(!default.is_valid("klm-1234"));
+        assert!(!default.rejects("klm-1234"));
+
+        let config: Config = toml::from_str("[mission_id]\npattern = '^M[0-9]+$'\n").unwrap();
+        assert!(config.mission_id.is_valid("M42"));
+        assert!(config.mission_id.rejects("KLM-1234"));
+        assert!(toml::from_str::<Config>("[mission_id]\n").unwrap().mission_id.enforced);
+        assert!(!toml::from_str::<Config>("").unwrap().mission_id.enforced);
+        assert!(toml::from_str::<Config>("[mission_id]\npattern = '('\n").is_err());
+    }
+
+    #[test]
+    fn test_config_rejects_unknown_keys() {
+        assert!(toml::from_str::<Config>("[ranges]\ncrew = { min = 1 }\n").is_err());
+        assert!(toml::from_str::<Config>("").is_ok());
+    }
+}
@@ -62,6 +62,51 @@ fn test_json_output() {
     assert_eq!(first_mission["duration_days"], 900);
 }
 
+#[test]
+fn test_ndjson_output() {
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--format")
+        .arg("ndjson")
+        .arg("--top")
+        .arg("3")
+        .arg("--ndjson-stats")
+        .arg("tests/test_data.log")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 4, "Should emit 3 missions plus a statistics record");
+
+    // Every line must be a standalone compact JSON document
+    for line in &lines {
+        serde_json::from_str::<serde_json::Value>(line).expect("Each line should be valid JSON");
+    }
+
+    let first: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(first["security_code"], "STU-901-FGH");
+
+    let last: serde_json::Value = serde_json::from_str(lines[3]).unwrap();
+    assert!(last.get("statistics").is_some());
+}
+
+#[test]
+fn test_ndjson_stats_requires_ndjson_format() {
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--format")
+        .arg("json")
+        .arg("--ndjson-stats")
+        .arg("tests/test_data.log")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--ndjson-stats requires --format ndjson"));
+}
+
 #[test]
 fn test_csv_output() {
     let output = Command::new("./target/release/mars-mission-analyzer")
@@ -82,6 +127,110 @@ fn test_csv_output() {
     assert!(stdout.contains("STU-901-FGH"));
 }
 
+#[test]
+fn test_csv_output_title_cases_labels() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("lower.log");
+    std::fs::write(&file_path, "2045-01-01 | KLM-0001 | mars | COMPLETED | 3 | 400 | 90.0 | ABC-123-XYZ\n").unwrap();
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--format")
+        .arg("csv")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains(",KLM-0001,Mars,Completed,"));
+}
+
+#[test]
+fn test_table_output() {
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--format")
+        .arg("table")
+        .arg("--top")
+        .arg("2")
+        .arg("tests/test_data.log")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 4, "Header, rule, and two rows expected");
+    assert!(lines[0].starts_with("Rank"));
+    assert!(lines[2].contains("STU-901-FGH"));
+    assert!(!stdout.contains('\x1b'), "Piped output should not be colored");
+}
+
+#[test]
+fn test_markdown_output() {
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--format")
+        .arg("markdown")
+        .arg("tests/test_data.log")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("| Rank | Date | Mission ID |"));
+    assert!(stdout.contains("| STU-901-FGH |"));
+}
+
+#[test]
+fn test_html_report() {
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--format")
+        .arg("html")
+        .arg("--top")
+        .arg("3")
+        .arg("tests/test_data.log")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("<!DOCTYPE html>"));
+    assert!(stdout.contains("<svg"), "Charts should be inline SVG");
+    assert!(stdout.contains("Rejection Breakdown"));
+    assert!(stdout.contains("STU-901-FGH"));
+    assert!(!stdout.contains("src=\"http"), "Report must not reference external assets");
+}
+
+#[test]
+fn test_parquet_output() {
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--format")
+        .arg("parquet")
+        .arg("--top")
+        .arg("3")
+        .arg("tests/test_data.log")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    assert!(output.stdout.starts_with(b"PAR1"));
+    assert!(output.stdout.ends_with(b"PAR1"));
+}
+
+#[test]
+fn test_arrow_output() {
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--format")
+        .arg("arrow")
+        .arg("tests/test_data.log")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    assert!(output.stdout.starts_with(b"ARROW1"));
+}
+
 #[test]
 fn test_top_n_missions() {
     let output = Command::new("./target/release/mars-mission-analyzer")
@@ -104,121 +253,1268 @@ fn test_top_n_missions() {
 }
 
 #[test]
-fn test_no_input_file_error() {
+fn test_export_sqlite() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("missions.db");
+
     let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--export-sqlite")
+        .arg(&db_path)
+        .arg("tests/test_data.log")
         .output()
         .expect("Failed to execute command");
 
-    assert!(!output.status.success(), "Should fail without input file");
+    assert!(output.status.success());
 
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    assert!(stderr.contains("No input file provided"));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Security Code: STU-901-FGH"), "Normal output is still printed");
+
+    let conn = rusqlite::Connection::open(&db_path).unwrap();
+    let total: i64 = conn.query_row("SELECT COUNT(*) FROM missions", [], |r| r.get(0)).unwrap();
+    let valid: i64 = conn.query_row("SELECT COUNT(*) FROM missions WHERE valid = 1", [], |r| r.get(0)).unwrap();
+    let runs: i64 = conn.query_row("SELECT valid_missions FROM runs", [], |r| r.get(0)).unwrap();
+
+    assert_eq!(total, 7, "Non-Mars and non-completed missions are exported too");
+    assert_eq!(valid, 4);
+    assert_eq!(runs, 4);
 }
 
 #[test]
-fn test_nonexistent_file_error() {
+fn test_group_by_destination_json() {
     let output = Command::new("./target/release/mars-mission-analyzer")
-        .arg("nonexistent_file.log")
+        .arg("--group-by")
+        .arg("destination")
+        .arg("--top")
+        .arg("2")
+        .arg("--format")
+        .arg("json")
+        .arg("tests/test_data.log")
         .output()
         .expect("Failed to execute command");
 
-    assert!(!output.status.success(), "Should fail with nonexistent file");
+    assert!(output.status.success());
 
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    assert!(stderr.contains("Failed to open file"));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value = serde_json::from_str(&stdout)
+        .expect("Output should be valid JSON");
+
+    assert_eq!(json["group_by"], "destination");
+    let groups = json["groups"].as_array().unwrap();
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0]["group"], "Mars");
+    assert_eq!(groups[0]["missions"].as_array().unwrap().len(), 2);
+    assert_eq!(groups[1]["group"], "Moon");
+    assert_eq!(groups[1]["missions"][0]["security_code"], "JKL-012-OPQ");
+    assert_eq!(groups[1]["missions"][0]["destination"], "Moon");
 }
 
 #[test]
-fn test_empty_file() {
-    let temp_dir = TempDir::new().unwrap();
-    let file_path = temp_dir.path().join("empty.log");
-    File::create(&file_path).unwrap();
+fn test_group_by_destination_csv_is_flattened() {
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--group-by")
+        .arg("destination")
+        .arg("--format")
+        .arg("csv")
+        .arg("tests/test_data.log")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert!(lines[0].starts_with("Group,Rank,"));
+    assert_eq!(lines.len(), 3, "Header plus the top mission of each destination");
+    assert!(lines[1].starts_with("Mars,1,"));
+    assert!(lines[2].starts_with("Moon,1,"));
+}
 
+#[test]
+fn test_stats_by_destination() {
     let output = Command::new("./target/release/mars-mission-analyzer")
-        .arg(&file_path)
+        .arg("stats")
+        .arg("--format")
+        .arg("json")
+        .arg("tests/test_data.log")
         .output()
         .expect("Failed to execute command");
 
-    assert!(!output.status.success(), "Should fail with empty file");
+    assert!(output.status.success());
 
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    assert!(stderr.contains("No valid completed Mars missions found"));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value = serde_json::from_str(&stdout)
+        .expect("Output should be valid JSON");
+
+    assert_eq!(json["group_by"], "destination");
+    let groups = json["groups"].as_array().unwrap();
+    assert_eq!(groups.len(), 2, "Mars and Moon");
+    assert_eq!(groups[0]["group"], "Mars");
+    assert_eq!(groups[0]["count"], 6, "Includes non-completed missions");
+    assert_eq!(groups[0]["duration"]["max"], 900.0);
 }
 
 #[test]
-fn test_file_with_only_comments() {
-    let temp_dir = TempDir::new().unwrap();
-    let file_path = temp_dir.path().join("comments.log");
-    let mut file = File::create(&file_path).unwrap();
-    writeln!(file, "# This is a comment").unwrap();
-    writeln!(file, "# Another comment").unwrap();
-    writeln!(file, "SYSTEM: metadata").unwrap();
+fn test_stats_by_status_csv() {
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("stats")
+        .arg("--by")
+        .arg("status")
+        .arg("--format")
+        .arg("csv")
+        .arg("tests/test_data.log")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
 
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("Group,Count,Duration Min"));
+    assert!(stdout.contains("\nCompleted,5,"));
+    assert!(stdout.contains("\nIn Progress,1,"));
+}
+
+#[test]
+fn test_histogram_ascii() {
     let output = Command::new("./target/release/mars-mission-analyzer")
-        .arg(&file_path)
+        .arg("histogram")
+        .arg("--bucket-width")
+        .arg("200")
+        .arg("tests/test_data.log")
         .output()
         .expect("Failed to execute command");
 
-    assert!(!output.status.success());
+    assert!(output.status.success());
 
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    assert!(stderr.contains("No valid completed Mars missions found"));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3, "500-699, 700-899, 900-1099");
+    assert!(lines[0].starts_with(" 500 - 699 | █"), "Labels are right-aligned");
+    assert!(lines[0].ends_with(" 2"));
 }
 
 #[test]
-fn test_file_with_no_mars_missions() {
-    let temp_dir = TempDir::new().unwrap();
-    let file_path = temp_dir.path().join("no_mars.log");
-    let mut file = File::create(&file_path).unwrap();
-    writeln!(file, "2045-07-12 | KLM-1234 | Jupiter | Completed | 5 | 387 | 98.7 | TRX-842-YHG").unwrap();
-    writeln!(file, "2045-08-15 | ABC-5678 | Venus | Completed | 3 | 200 | 95.0 | ABC-123-XYZ").unwrap();
+fn test_histogram_quantiles_json() {
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("histogram")
+        .arg("--quantiles")
+        .arg("2")
+        .arg("--format")
+        .arg("json")
+        .arg("tests/test_data.log")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value = serde_json::from_str(&stdout)
+        .expect("Output should be valid JSON");
+
+    assert_eq!(json["bucketing"], "quantile");
+    assert_eq!(json["total"], 4);
+    let buckets = json["buckets"].as_array().unwrap();
+    assert_eq!(buckets.len(), 2);
+    assert_eq!(buckets[0]["upper"], 600);
+    assert_eq!(buckets[0]["count"], 2);
+}
 
+#[test]
+fn test_histogram_rejects_conflicting_bucketing() {
     let output = Command::new("./target/release/mars-mission-analyzer")
-        .arg(&file_path)
+        .arg("histogram")
+        .arg("--bucket-width")
+        .arg("100")
+        .arg("--quantiles")
+        .arg("4")
+        .arg("tests/test_data.log")
         .output()
         .expect("Failed to execute command");
 
     assert!(!output.status.success());
-
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    assert!(stderr.contains("No Mars missions found"));
 }
 
 #[test]
-fn test_mars_missions_but_none_completed() {
+fn test_histogram_rejects_too_many_buckets() {
     let temp_dir = TempDir::new().unwrap();
-    let file_path = temp_dir.path().join("no_completed.log");
-    let mut file = File::create(&file_path).unwrap();
-    writeln!(file, "2045-07-12 | KLM-1234 | Mars | Failed | 5 | 387 | 98.7 | TRX-842-YHG").unwrap();
-    writeln!(file, "2045-08-15 | ABC-5678 | Mars | InProgress | 3 | 200 | 95.0 | ABC-123-XYZ").unwrap();
+    let log = temp_dir.path().join("wide.log");
+    std::fs::write(
+        &log,
+        "2045-01-01 | AAA-0001 | Mars | Completed | 3 | 1 | 90.0 | ABC-123-XYZ\n\
+         2045-01-02 | BBB-0002 | Mars | Completed | 3 | 4000000000 | 90.0 | ABC-123-XYZ\n",
+    )
+    .unwrap();
 
     let output = Command::new("./target/release/mars-mission-analyzer")
-        .arg(&file_path)
+        .arg("histogram")
+        .arg("--bucket-width")
+        .arg("1")
+        .arg(&log)
         .output()
         .expect("Failed to execute command");
 
     assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("at most 10000 are allowed"));
+}
 
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    assert!(stderr.contains("none with 'Completed' status"));
+#[test]
+fn test_histogram_rejects_zero_bucket_width() {
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("histogram")
+        .arg("--bucket-width")
+        .arg("0")
+        .arg("tests/test_data.log")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--bucket-width"));
 }
 
 #[test]
-fn test_completed_mars_mission_with_invalid_code() {
-    let temp_dir = TempDir::new().unwrap();
-    let file_path = temp_dir.path().join("invalid_code.log");
-    let mut file = File::create(&file_path).unwrap();
-    writeln!(file, "2045-07-12 | KLM-1234 | Mars | Completed | 5 | 387 | 98.7 | INVALID").unwrap();
+fn test_trends_quarterly_csv() {
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("trends")
+        .arg("--period")
+        .arg("quarter")
+        .arg("--format")
+        .arg("csv")
+        .arg("tests/test_data.log")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "Period,Missions,Completed,Completion Rate (%),Avg Duration (days),Avg Success Rate");
+    assert_eq!(lines.len(), 4, "Q1, Q2 and Q3 of 2045");
+    assert!(lines[1].starts_with("2045-Q1,3,2,"));
+}
 
+#[test]
+fn test_trends_destination_json() {
     let output = Command::new("./target/release/mars-mission-analyzer")
-        .arg(&file_path)
+        .arg("trends")
+        .arg("--destination")
+        .arg("mars")
+        .arg("--format")
+        .arg("json")
+        .arg("tests/test_data.log")
         .output()
         .expect("Failed to execute command");
 
-    assert!(!output.status.success());
+    assert!(output.status.success());
 
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    assert!(stderr.contains("all had invalid data"));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value = serde_json::from_str(&stdout)
+        .expect("Output should be valid JSON");
+
+    let series = json["series"].as_array().unwrap();
+    assert_eq!(series.len(), 1);
+    assert_eq!(series[0]["period"], "2045");
+    assert_eq!(series[0]["missions"], 6);
+    assert_eq!(series[0]["completed"], 4);
+}
+
+fn write_outlier_log(dir: &TempDir) -> std::path::PathBuf {
+    let file_path = dir.path().join("outliers.log");
+    let mut file = File::create(&file_path).unwrap();
+    for (idx, duration) in [400, 410, 390, 405, 395, 420].iter().enumerate() {
+        writeln!(file, "2045-01-0{} | OK-000{} | Mars | Completed | 4 | {} | 90.0 | ABC-123-XYZ", idx + 1, idx, duration).unwrap();
+    }
+    writeln!(file, "2045-02-01 | BAD-0001 | Mars | Completed | 4 | 90000 | 90.0 | ZZZ-999-ZZZ").unwrap();
+    file_path
+}
+
+#[test]
+fn test_anomalies_json() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = write_outlier_log(&temp_dir);
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("anomalies")
+        .arg("--format")
+        .arg("json")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value = serde_json::from_str(&stdout)
+        .expect("Output should be valid JSON");
+
+    assert_eq!(json["method"], "mad");
+    let anomalies = json["anomalies"].as_array().unwrap();
+    assert_eq!(anomalies.len(), 1);
+    assert_eq!(anomalies[0]["mission_id"], "BAD-0001");
+    assert_eq!(anomalies[0]["field"], "duration");
+    assert!(anomalies[0]["z_score"].as_f64().unwrap() > 3.5);
+}
+
+#[test]
+fn test_exclude_outliers_changes_winner() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = write_outlier_log(&temp_dir);
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Mission Length: 90000 days"));
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--exclude-outliers")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Mission Length: 420 days"));
+}
+
+#[test]
+fn test_no_input_file_error() {
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success(), "Should fail without input file");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No input file provided"));
+}
+
+#[test]
+fn test_nonexistent_file_error() {
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("nonexistent_file.log")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success(), "Should fail with nonexistent file");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Failed to open file"));
+}
+
+#[test]
+fn test_empty_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("empty.log");
+    File::create(&file_path).unwrap();
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success(), "Should fail with empty file");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No valid completed Mars missions found"));
+}
+
+#[test]
+fn test_file_with_only_comments() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("comments.log");
+    let mut file = File::create(&file_path).unwrap();
+    writeln!(file, "# This is a comment").unwrap();
+    writeln!(file, "# Another comment").unwrap();
+    writeln!(file, "SYSTEM: metadata").unwrap();
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No valid completed Mars missions found"));
+}
+
+#[test]
+fn test_file_with_no_mars_missions() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("no_mars.log");
+    let mut file = File::create(&file_path).unwrap();
+    writeln!(file, "2045-07-12 | KLM-1234 | Jupiter | Completed | 5 | 387 | 98.7 | TRX-842-YHG").unwrap();
+    writeln!(file, "2045-08-15 | ABC-5678 | Venus | Completed | 3 | 200 | 95.0 | ABC-123-XYZ").unwrap();
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No Mars missions found"));
+}
+
+#[test]
+fn test_mars_missions_but_none_completed() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("no_completed.log");
+    let mut file = File::create(&file_path).unwrap();
+    writeln!(file, "2045-07-12 | KLM-1234 | Mars | Failed | 5 | 387 | 98.7 | TRX-842-YHG").unwrap();
+    writeln!(file, "2045-08-15 | ABC-5678 | Mars | InProgress | 3 | 200 | 95.0 | ABC-123-XYZ").unwrap();
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("none with 'Completed' status"));
+}
+
+#[test]
+fn test_completed_mars_mission_with_invalid_code() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("invalid_code.log");
+    let mut file = File::create(&file_path).unwrap();
+    writeln!(file, "2045-07-12 | KLM-1234 | Mars | Completed | 5 | 387 | 98.7 | INVALID").unwrap();
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("all had invalid data"));
+}
+
+fn write_range_log(dir: &TempDir) -> std::path::PathBuf {
+    let file_path = dir.path().join("ranges.log");
+    let mut file = File::create(&file_path).unwrap();
+    writeln!(file, "2045-01-01 | NO-CREW | Mars | Completed | 0 | 9000 | 90.0 | ABC-123-XYZ").unwrap();
+    writeln!(file, "2045-01-02 | TOO-SURE | Mars | Completed | 4 | 8000 | 250.0 | ABC-123-XYZ").unwrap();
+    writeln!(file, "2045-01-03 | GOOD-001 | Mars | Completed | 4 | 700 | 90.0 | ABC-123-XYZ").unwrap();
+    file_path
+}
+
+#[test]
+fn test_out_of_range_values_are_rejected() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = write_range_log(&temp_dir);
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--format")
+        .arg("json")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["missions"][0]["mission_id"], "GOOD-001");
+    assert_eq!(json["statistics"]["rejections"]["crew_size_out_of_range"], 1);
+    assert_eq!(json["statistics"]["rejections"]["success_rate_out_of_range"], 1);
+}
+
+#[test]
+fn test_config_file_overrides_ranges() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = write_range_log(&temp_dir);
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(&config_path, "[ranges]\ncrew_size = { min = 0 }\nduration = { max = 8500 }\n").unwrap();
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--verbose")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stdout.contains("GOOD-001"));
+    assert!(stderr.contains("Line 1 has duration out of range (<= 8500): 9000"));
+    assert!(stderr.contains("Line 2 has success_rate out of range (0..=100): 250"));
+}
+
+#[test]
+fn test_invalid_config_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = write_range_log(&temp_dir);
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(&config_path, "[ranges]\ncrew = { min = 1 }\n").unwrap();
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--config")
+        .arg(&config_path)
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid config file"));
+}
+
+#[test]
+fn test_rules_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = write_range_log(&temp_dir);
+    let rules_path = temp_dir.path().join("rules.toml");
+    std::fs::write(
+        &rules_path,
+        r#"
+[[rules]]
+name = "long-missions-only"
+field = "duration"
+min = 8500
+
+[[rules]]
+name = "id-has-digits"
+field = "mission_id"
+pattern = '\d'
+severity = "warn"
+"#,
+    )
+    .unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(&config_path, "[ranges]\ncrew_size = {}\nsuccess_rate = {}\n").unwrap();
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--rules")
+        .arg(&rules_path)
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--verbose")
+        .arg("--format")
+        .arg("json")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(json["missions"][0]["mission_id"], "NO-CREW");
+    assert_eq!(json["statistics"]["rejections"]["rule_violations"], 2);
+    // Two id-has-digits warnings, plus NO-CREW missing the default ID pattern
+    assert_eq!(json["statistics"]["warnings"], 3);
+    assert!(stderr.contains("Line 1 violates warn rule 'id-has-digits'"));
+    assert!(stderr.contains("Line 3 violates error rule 'long-missions-only': duration 700 is below 8500"));
+}
+
+#[test]
+fn test_rules_override_default_checks() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("codes.log");
+    let mut file = File::create(&file_path).unwrap();
+    writeln!(file, "2045-01-01 | LEG-0001 | Mars | Completed | 3 | 400 | 90.0 | AB-12").unwrap();
+    writeln!(file, "2045-01-02 | MON-0001 | Moon | Completed | 3 | 5 | 90.0 | ABC-123-XYZ").unwrap();
+    let rules_path = temp_dir.path().join("rules.toml");
+    std::fs::write(
+        &rules_path,
+        r#"
+[[rules]]
+name = "invalid_security_code"
+enabled = false
+
+[[rules]]
+name = "zero_duration"
+when = { field = "status", allowed = ["Completed"] }
+field = "duration"
+min = 10
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--rules")
+        .arg(&rules_path)
+        .arg("--format")
+        .arg("json")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["missions"][0]["mission_id"], "LEG-0001");
+    assert_eq!(json["statistics"]["rejections"]["invalid_security_code"], 0);
+    assert_eq!(json["statistics"]["rejections"]["zero_duration"], 1);
+}
+
+#[test]
+fn test_validate_reports_every_record() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("lint.log");
+    let mut file = File::create(&file_path).unwrap();
+    writeln!(file, "# comment").unwrap();
+    writeln!(file, "2045-01-01 | V-1 | Venus | Completed | 3 | 0 | 50.0 | ABC-123-XYZ").unwrap();
+    writeln!(file, "not a record").unwrap();
+    writeln!(file, "2045-01-03 | M-1 | Mars | Completed | 3 | 10 | 50.0 | ABC-123-XYZ").unwrap();
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("validate")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(output.status.code(), Some(1));
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(":2: error: completed mission has a duration of 0 days [zero_duration]"));
+    assert!(stdout.contains(":3: error: expected 8 '|'-separated fields, found 1 [invalid_format]"));
+    assert!(stdout.contains("Checked 3 records: 2 errors, 0 warnings (2 invalid records)"));
+}
+
+#[test]
+fn test_validate_json_clean_file() {
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("validate")
+        .arg("--format")
+        .arg("json")
+        .arg("tests/test_data.log")
+        .output()
+        .expect("Failed to execute command");
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let errors = json["summary"]["errors"].as_u64().unwrap();
+    assert_eq!(output.status.success(), errors == 0);
+    assert_eq!(json["diagnostics"].as_array().unwrap().len() as u64, errors + json["summary"]["warnings"].as_u64().unwrap());
+}
+
+#[test]
+fn test_fmt_check_and_in_place() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("messy.log");
+    std::fs::write(
+        &file_path,
+        "# keep   me\n2045/01/05|A-1|  mars |completed| 3 | 500 | 95.5 |ABC-123-XYZ\n",
+    )
+    .unwrap();
+
+    let check = Command::new("./target/release/mars-mission-analyzer")
+        .arg("fmt")
+        .arg("--check")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(check.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&check.stdout).contains("messy.log:2: not in canonical form"));
+
+    let rewrite = Command::new("./target/release/mars-mission-analyzer")
+        .arg("fmt")
+        .arg("--in-place")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(rewrite.status.success());
+    assert_eq!(
+        std::fs::read_to_string(&file_path).unwrap(),
+        "# keep   me\n2045-01-05 | A-1 | Mars | Completed | 3 | 500 | 95.5 | ABC-123-XYZ\n"
+    );
+
+    let recheck = Command::new("./target/release/mars-mission-analyzer")
+        .arg("fmt")
+        .arg("--check")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(recheck.status.success());
+    assert!(recheck.stdout.is_empty());
+}
+
+#[test]
+fn test_diff_logs() {
+    let temp_dir = TempDir::new().unwrap();
+    let old_path = temp_dir.path().join("old.log");
+    let new_path = temp_dir.path().join("new.log");
+    std::fs::write(
+        &old_path,
+        "2045-01-01 | A | Mars | Completed | 3 | 500 | 90.0 | ABC-123-XYZ\n\
+         2045-01-02 | B | Mars | Completed | 3 | 400 | 90.0 | ABC-123-XYZ\n",
+    )
+    .unwrap();
+    std::fs::write(
+        &new_path,
+        "2045-01-01 | A | Mars | Failed | 3 | 500 | 90.0 | ABC-123-XYZ\n\
+         2045-01-02 | B | Mars | Completed | 3 | 400 | 90.0 | ABC-123-XYZ\n\
+         2045-01-03 | C | Mars | Completed | 3 | 100 | 90.0 | ABC-123-XYZ\n",
+    )
+    .unwrap();
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("diff")
+        .arg(&old_path)
+        .arg(&new_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(output.status.code(), Some(1));
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("  + C (line 3)"));
+    assert!(stdout.contains("  ~ A: status Completed -> Failed"));
+    assert!(stdout.contains("Top 1 ranking changed:\n  old: A\n  new: B"));
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("diff")
+        .arg("--format")
+        .arg("json")
+        .arg(&old_path)
+        .arg(&old_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["added"].as_array().unwrap().len(), 0);
+    assert_eq!(json["ranking"]["changed"], false);
+
+    // Errors must not be mistaken for "the logs differ"
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("diff")
+        .arg(&old_path)
+        .arg(temp_dir.path().join("missing.log"))
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(output.status.code(), Some(2));
+}
+
+fn write_overlapping_logs(dir: &TempDir) -> (std::path::PathBuf, std::path::PathBuf) {
+    let first = dir.path().join("first.log");
+    let second = dir.path().join("second.log");
+    std::fs::write(
+        &first,
+        "2045-01-01 | A | Mars | Completed | 3 | 900 | 90.0 | ABC-123-XYZ\n\
+         2045-01-02 | B | Mars | Completed | 3 | 400 | 90.0 | ABC-123-XYZ\n",
+    )
+    .unwrap();
+    std::fs::write(
+        &second,
+        "2045-02-01 | A | Mars | Failed | 3 | 900 | 90.0 | ABC-123-XYZ\n\
+         2045-01-02 | B | Mars | Completed | 3 | 400 | 90.0 | ABC-123-XYZ\n",
+    )
+    .unwrap();
+    (first, second)
+}
+
+#[test]
+fn test_merge_policies() {
+    let temp_dir = TempDir::new().unwrap();
+    let (first, second) = write_overlapping_logs(&temp_dir);
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("merge")
+        .arg("--policy")
+        .arg("prefer-completed")
+        .arg(&first)
+        .arg(&second)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let records: Vec<&str> = stdout.lines().filter(|l| !l.starts_with('#')).collect();
+    assert_eq!(records, vec![
+        "2045-01-01 | A | Mars | Completed | 3 | 900 | 90.0 | ABC-123-XYZ",
+        "2045-01-02 | B | Mars | Completed | 3 | 400 | 90.0 | ABC-123-XYZ",
+    ]);
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("merge")
+        .arg("--policy")
+        .arg("report")
+        .arg(&first)
+        .arg(&second)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Conflict: A differs in date, status"));
+}
+
+#[test]
+fn test_merge_with_itself_matches_fmt() {
+    let temp_dir = TempDir::new().unwrap();
+    let log = temp_dir.path().join("missions.log");
+    std::fs::write(
+        &log,
+        "2045/01/01 |  AAA-0001 | mars | COMPLETED | 03 | 900 | 85.50 | ABC-123-XYZ\n\
+         2045-01-02 | BBB-0002 | Mars | Completed | 3 | 400 | 90.0 | ABC-123-XYZ\n",
+    )
+    .unwrap();
+
+    let merged = Command::new("./target/release/mars-mission-analyzer")
+        .arg("merge")
+        .arg(&log)
+        .arg(&log)
+        .output()
+        .expect("Failed to execute command");
+    let formatted = Command::new("./target/release/mars-mission-analyzer")
+        .arg("fmt")
+        .arg(&log)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(merged.status.success());
+    let merged = String::from_utf8_lossy(&merged.stdout);
+    let records: Vec<&str> = merged.lines().filter(|l| !l.starts_with('#')).collect();
+    let formatted = String::from_utf8_lossy(&formatted.stdout);
+    assert_eq!(records, formatted.lines().collect::<Vec<&str>>());
+    assert_eq!(records[0], "2045-01-01 | AAA-0001 | Mars | Completed | 3 | 900 | 85.50 | ABC-123-XYZ");
+}
+
+#[test]
+fn test_merge_skips_empty_shard() {
+    let temp_dir = TempDir::new().unwrap();
+    let empty = temp_dir.path().join("empty.log");
+    std::fs::write(&empty, "# nothing yet\n").unwrap();
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("merge")
+        .arg(&empty)
+        .arg("tests/test_data.log")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.lines().filter(|l| !l.starts_with('#')).count(), 7);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No parseable missions found; skipping it."));
+}
+
+#[test]
+fn test_dedupe_report_json() {
+    let temp_dir = TempDir::new().unwrap();
+    let (first, second) = write_overlapping_logs(&temp_dir);
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("dedupe")
+        .arg("--format")
+        .arg("json")
+        .arg(&first)
+        .arg(&second)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let duplicates = json["duplicates"].as_array().unwrap();
+    assert_eq!(duplicates.len(), 2);
+    assert_eq!(duplicates[0]["mission_id"], "A");
+    assert_eq!(duplicates[0]["kept"]["source"], 1);
+    assert_eq!(duplicates[1]["conflicting_fields"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn test_dedupe_before_ranking() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("dupes.log");
+    std::fs::write(
+        &file_path,
+        "2045-01-01 | A | Mars | Completed | 3 | 900 | 90.0 | ABC-123-XYZ\n\
+         2045-01-02 | B | Mars | Completed | 3 | 400 | 90.0 | ABC-123-XYZ\n\
+         2045-02-01 | A | Mars | Failed | 3 | 900 | 90.0 | ABC-123-XYZ\n",
+    )
+    .unwrap();
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--dedupe")
+        .arg("latest-date")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("400 days"));
+}
+
+fn write_lifecycle_log(dir: &TempDir) -> std::path::PathBuf {
+    let file_path = dir.path().join("lifecycle.log");
+    std::fs::write(
+        &file_path,
+        "# lifecycle\n\
+         2045-01-01 | KLM-0001 | Mars | Planned | 3 | 0 | 0.0 | ABC-123-XYZ\n\
+         2045-01-01 | KLM-0002 | Mars | Completed | 3 | 800 | 90.0 | ABC-123-XYZ\n\
+         2045-01-01 | KLM-0001 | Mars | Completed | 3 | 500 | 95.0 | ABC-123-XYZ\n\
+         2045-01-01 | KLM-0002 | Mars | In Progress | 3 | 800 | 90.0 | ABC-123-XYZ\n",
+    )
+    .unwrap();
+    file_path
+}
+
+#[test]
+fn test_final_state_ranking() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = write_lifecycle_log(&temp_dir);
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--final-state")
+        .arg("--verbose")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("500 days"));
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("Mission KLM-0002 moves from Completed to In Progress (line 3 -> line 5)"));
+}
+
+#[test]
+fn test_timeline_subcommand() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = write_lifecycle_log(&temp_dir);
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("timeline")
+        .arg("KLM-0001")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("Timeline for KLM-0001 (2 entries)"));
+    assert!(stdout.contains("  line 4: 2045-01-01 Completed  <- final state"));
+    assert!(stdout.contains("    2045-01-01 | KLM-0001 | Mars | Planned"));
+
+    let missing = Command::new("./target/release/mars-mission-analyzer")
+        .arg("timeline")
+        .arg("NOPE")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!missing.status.success());
+}
+
+#[test]
+fn test_mission_id_pattern_rejects_only_when_configured() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("ids.log");
+    std::fs::write(
+        &file_path,
+        "2045-01-01 | bad_id | Mars | Completed | 3 | 900 | 90.0 | ABC-123-XYZ\n\
+         2045-01-02 | KLM-1234 | Mars | Completed | 3 | 400 | 90.0 | ABC-123-XYZ\n",
+    )
+    .unwrap();
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--verbose")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("900 days"));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Line 1 has invalid mission ID format: bad_id"));
+    assert!(stderr.contains("Warnings: 1"));
+
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(&config_path, "[mission_id]\npattern = '^[a-z_]+$'\n").unwrap();
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--format")
+        .arg("json")
+        .arg(&file_path)
+        .output()
+        .expect("Failed to execute command");
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["missions"].as_array().unwrap().len(), 1);
+    assert_eq!(json["statistics"]["rejections"]["invalid_mission_id"], 1);
+}
+
+#[test]
+fn test_show_by_id_and_prefix() {
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("show")
+        .arg("TST-0004")
+        .arg("tests/test_data.log")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("TST-0004 (tests/test_data.log:10)"));
+    assert!(stdout.contains("  Destination:   Moon"));
+    assert!(stdout.contains("  Ranking:       excluded (not_mars)"));
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("show")
+        .arg("--format")
+        .arg("json")
+        .arg("tst-000")
+        .arg("tests/test_data.log")
+        .output()
+        .expect("Failed to execute command");
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(json.as_array().unwrap().len() > 1);
+    assert_eq!(json[0]["mission_id"], "TST-0001");
+    assert_eq!(json[0]["file"], "tests/test_data.log");
+}
+
+#[test]
+fn test_explain_by_line_and_mission_id() {
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--explain")
+        .arg("8")
+        .arg("tests/test_data.log")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("  status         fail     In Progress"));
+    assert!(stdout.ends_with("Verdict: excluded at status\n"));
+
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .arg("--explain")
+        .arg("TST-0003")
+        .arg("--format")
+        .arg("json")
+        .arg("tests/test_data.log")
+        .output()
+        .expect("Failed to execute command");
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let ranking = json[0]["steps"].as_array().unwrap().last().unwrap().clone();
+    assert_eq!(json[0]["included"], false);
+    assert_eq!(ranking["stage"], "ranking");
+    assert_eq!(ranking["detail"], "rank 2 of 4, outside --top 1");
+}
+
+#[test]
+fn test_follow_emits_ndjson_deltas() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("live.log");
+    let mut file = File::create(&path).unwrap();
+    writeln!(file, "2045-01-01 | FLW-0001 | Mars | Completed | 3 | 100 | 90.0 | ABC-123-XYZ").unwrap();
+    drop(file);
+
+    let mut child = Command::new("./target/release/mars-mission-analyzer")
+        .args(["--follow", "--format", "ndjson"])
+        .arg(&path)
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .expect("Failed to execute command");
+
+    std::thread::sleep(std::time::Duration::from_millis(1000));
+    let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+    writeln!(file, "2045-01-02 | FLW-0002 | Mars | Completed | 3 | 900 | 90.0 | ABC-123-XYZ").unwrap();
+    drop(file);
+    std::thread::sleep(std::time::Duration::from_millis(1000));
+
+    child.kill().unwrap();
+    let output = child.wait_with_output().unwrap();
+    let records: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0]["change"], "added");
+    assert_eq!(records[0]["mission_id"], "FLW-0001");
+    assert_eq!(records[1]["change"], "removed");
+    assert_eq!(records[1]["mission_id"], "FLW-0001");
+    assert_eq!(records[2]["change"], "added");
+    assert_eq!(records[2]["mission_id"], "FLW-0002");
+}
+
+#[test]
+fn test_state_resumes_from_checkpoint() {
+    let dir = TempDir::new().unwrap();
+    let log = dir.path().join("missions.log");
+    let state = dir.path().join("state.json");
+    let mut file = File::create(&log).unwrap();
+    writeln!(file, "# header").unwrap();
+    writeln!(file, "2045-01-01 | STA-0001 | Mars | Completed | 3 | 300 | 90.0 | ABC-123-XYZ").unwrap();
+    writeln!(file, "2045-01-02 | STA-0002 | Mars | Failed | 3 | 900 | 90.0 | ABC-123-XYZ").unwrap();
+    drop(file);
+
+    let run = |extra: &[&str]| {
+        Command::new("./target/release/mars-mission-analyzer")
+            .args(["--format", "json", "--verbose"])
+            .args(extra)
+            .arg(&log)
+            .output()
+            .expect("Failed to execute command")
+    };
+    let state_arg = ["--state", state.to_str().unwrap()];
+
+    assert!(run(&state_arg).status.success());
+    assert!(state.exists());
+
+    let mut file = std::fs::OpenOptions::new().append(true).open(&log).unwrap();
+    writeln!(file, "2045-01-03 | STA-0003 | Mars | Completed | 3 | 500 | 90.0 | ABC-123-XYZ").unwrap();
+    drop(file);
+
+    let resumed = run(&state_arg);
+    assert!(resumed.status.success());
+    assert!(String::from_utf8_lossy(&resumed.stderr).contains("Resuming after line 3"));
+
+    let resumed: serde_json::Value = serde_json::from_slice(&resumed.stdout).unwrap();
+    let full: serde_json::Value = serde_json::from_slice(&run(&[]).stdout).unwrap();
+    assert_eq!(resumed, full);
+    assert_eq!(resumed["missions"][0]["mission_id"], "STA-0003");
+
+    std::fs::write(&log, "2045-01-01 | NEW-0001 | Mars | Completed | 3 | 100 | 90.0 | ABC-123-XYZ\n").unwrap();
+    let rewritten = run(&state_arg);
+    assert!(String::from_utf8_lossy(&rewritten.stderr).contains("rescanning from the start"));
+    let rewritten: serde_json::Value = serde_json::from_slice(&rewritten.stdout).unwrap();
+    assert_eq!(rewritten["statistics"]["total_lines"], 1);
+}
+
+#[test]
+fn test_index_is_used_until_log_changes() {
+    let dir = TempDir::new().unwrap();
+    let log = dir.path().join("missions.log");
+    let mut file = File::create(&log).unwrap();
+    writeln!(file, "2045-01-01 | IDX-0001 | Mars | Completed | 3 | 300 | 90.0 | ABC-123-XYZ").unwrap();
+    writeln!(file, "2045-01-02 | IDX-0002 | Mars | Completed | 3 | 500 | 90.0 | ABC-123-XYZ").unwrap();
+    drop(file);
+
+    let run = |args: &[&str]| {
+        Command::new("./target/release/mars-mission-analyzer")
+            .args(args)
+            .arg(&log)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let before = run(&["--format", "json"]);
+    let indexed = run(&["index"]);
+    assert!(indexed.status.success());
+    assert!(String::from_utf8_lossy(&indexed.stdout).contains("Indexed 2 lines (2 parsed missions)"));
+    assert!(dir.path().join("missions.log.idx").exists());
+
+    // Queries read the index and give the same answers
+    assert_eq!(run(&["--format", "json"]).stdout, before.stdout);
+    let stats = run(&["--format", "json", "stats"]);
+    assert!(stats.status.success());
+
+    // Appending makes the index stale, so the log is parsed again
+    let mut file = std::fs::OpenOptions::new().append(true).open(&log).unwrap();
+    writeln!(file, "2045-01-03 | IDX-0003 | Mars | Completed | 3 | 900 | 90.0 | ABC-123-XYZ").unwrap();
+    drop(file);
+    let after = run(&["--format", "json", "--verbose"]);
+    assert!(String::from_utf8_lossy(&after.stderr).contains("run `index` to refresh"));
+    let json: serde_json::Value = serde_json::from_slice(&after.stdout).unwrap();
+    assert_eq!(json["missions"][0]["mission_id"], "IDX-0003");
+}
+
+#[test]
+fn test_repl_answers_piped_queries() {
+    let dir = TempDir::new().unwrap();
+    let log = dir.path().join("missions.log");
+    let mut file = File::create(&log).unwrap();
+    writeln!(file, "2045-01-01 | REP-0001 | Mars | Completed | 3 | 300 | 90.0 | ABC-123-XYZ").unwrap();
+    writeln!(file, "2046-01-02 | REP-0002 | Mars | Completed | 3 | 500 | 80.0 | ABC-123-XYZ").unwrap();
+    writeln!(file, "2046-01-03 | REP-0003 | Jupiter | Completed | 4 | 900 | 99.0 | ABC-123-XYZ").unwrap();
+    drop(file);
+
+    let mut child = Command::new("./target/release/mars-mission-analyzer")
+        .args(["--format", "csv", "repl"])
+        .arg(&log)
+        .env("HOME", dir.path())
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .expect("Failed to execute command");
+
+    let script = "top 1 by success_rate\n\
+                  top 5 where destination=Jupiter\n\
+                  launch\n\
+                  format json\n\
+                  top 2 where destination=Jupiter\n\
+                  stats by year\n\
+                  quit\n";
+    child.stdin.take().unwrap().write_all(script.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(output.status.success());
+    assert!(stdout.contains("1,2045-01-01,REP-0001,"), "Highest success rate on Mars first: {}", stdout);
+    assert!(stdout.contains("1,2046-01-03,REP-0003,Jupiter,"), "Jupiter missions are ranked when asked for");
+    assert!(stdout.contains("\"destination\": \"Jupiter\""), "Non-Mars missions keep their destination: {}", stdout);
+    assert!(!stdout.contains("\"destination\": \"Mars\""));
+    assert!(stdout.contains("\"group_by\": \"year\""));
+    assert!(stderr.contains("unknown command 'launch'"));
+}
+
+#[test]
+fn test_tui_reports_missing_file_before_taking_over_terminal() {
+    let output = Command::new("./target/release/mars-mission-analyzer")
+        .args(["tui", "nonexistent_file.log"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("ERROR: Failed to read nonexistent_file.log"));
+}
+
+#[test]
+fn test_serve_answers_http_requests() {
+    use std::io::{BufRead, BufReader, Read};
+
+    let dir = TempDir::new().unwrap();
+    let log = dir.path().join("missions.log");
+    let mut file = File::create(&log).unwrap();
+    writeln!(file, "2045-01-01 | SRV-0001 | Mars | Completed | 3 | 300 | 90.0 | ABC-123-XYZ").unwrap();
+    drop(file);
+
+    let mut child = Command::new("./target/release/mars-mission-analyzer")
+        .args(["serve", "--port", "0"])
+        .arg(&log)
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .expect("Failed to execute command");
+
+    let mut banner = String::new();
+    BufReader::new(child.stderr.take().unwrap()).read_line(&mut banner).unwrap();
+    let addr = banner.trim().strip_prefix("Listening on http://").expect("listening banner").to_string();
+
+    let mut stream = std::net::TcpStream::connect(&addr).unwrap();
+    write!(stream, "GET /missions/srv-0001 HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert!(addr.starts_with("127.0.0.1:"), "Binds to loopback only");
+    assert!(response.starts_with("HTTP/1.1 200"));
+    let body = response.split("\r\n\r\n").nth(1).unwrap();
+    let json: serde_json::Value = serde_json::from_str(body).unwrap();
+    assert_eq!(json[0]["mission_id"], "SRV-0001");
+    assert_eq!(json[0]["rejection"], serde_json::Value::Null);
 }
 
 #[test]
@@ -249,4 +1545,5 @@ fn test_help_flag() {
     assert!(stdout.contains("--verbose"));
     assert!(stdout.contains("--format"));
     assert!(stdout.contains("--top"));
+    assert!(stdout.contains("stats"));
 }